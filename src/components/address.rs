@@ -0,0 +1,130 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::{AuthorName, Profiles};
+use crate::components::note_content::transform_text_to_html;
+use crate::helper::address::{address_title, calendar_span, list_items, tag_value, ListItem};
+use crate::helper::profiles::{profile_path, short_npub, website_url};
+
+const CARD_CLASS: &str = "block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 p-6 flex flex-col space-y-3 text-gray-700 dark:text-gray-200";
+
+#[component]
+fn Byline(event: Event) -> impl IntoView {
+    let profile = event.pubkey.to_hex();
+    view! {
+        <div class="flex items-center space-x-2 text-xs">
+            <Profiles profile=profile.clone() order=1/>
+            <AuthorName profile=profile/>
+        </div>
+    }
+}
+
+/// NIP-53 stream: status, cover, summary and where to watch.
+#[component]
+pub fn LiveEventCard(event: Event) -> impl IntoView {
+    let status = match tag_value(&event, "status").as_deref() {
+        Some("live") => "🔴 กำลังถ่ายทอดสด",
+        Some("ended") => "จบแล้ว",
+        _ => "เร็ว ๆ นี้",
+    };
+    let starts = tag_value(&event, "starts")
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .map(|secs| Timestamp::from(secs).to_human_datetime());
+    view! {
+        <div class=CARD_CLASS>
+            {tag_value(&event, "image").map(|url| view! { <img src=url alt="cover" class="w-full max-h-80 object-cover rounded-lg"/> })}
+            <div class="flex items-center justify-between">
+                <h1 class="text-2xl font-bold">{address_title(&event)}</h1>
+                <span class="text-sm">{status}</span>
+            </div>
+            <Byline event=event.clone()/>
+            {starts.map(|starts| view! { <span class="text-xs text-gray-400">{starts}</span> })}
+            {tag_value(&event, "summary").map(|summary| view! { <p>{summary}</p> })}
+            {tag_value(&event, "streaming")
+                .and_then(|url| website_url(&url))
+                .map(|url| {
+                    view! {
+                        <a href=url.clone() target="_blank" class="text-blue-500 break-all">
+                            {url}
+                        </a>
+                    }
+                })}
+        </div>
+    }
+}
+
+/// NIP-52 date or time based event.
+#[component]
+pub fn CalendarCard(event: Event) -> impl IntoView {
+    let (start, end) = calendar_span(&event);
+    let when = match (start, end) {
+        (Some(start), Some(end)) => format!("{} – {}", start, end),
+        (Some(start), None) => start,
+        _ => String::new(),
+    };
+    view! {
+        <div class=CARD_CLASS>
+            {tag_value(&event, "image").map(|url| view! { <img src=url alt="cover" class="w-full max-h-80 object-cover rounded-lg"/> })}
+            <h1 class="text-2xl font-bold">{address_title(&event)}</h1>
+            <Byline event=event.clone()/>
+            <span class="text-sm">"📅 " {when}</span>
+            {tag_value(&event, "location").map(|location| view! { <span class="text-sm">"📍 " {location}</span> })}
+            <p class="text-sm">{transform_text_to_html(event.content())}</p>
+        </div>
+    }
+}
+
+/// NIP-51 list or set, public entries only.
+#[component]
+pub fn ListCard(event: Event) -> impl IntoView {
+    let items = list_items(&event)
+        .into_iter()
+        .map(|item| {
+            let (href, text) = match item {
+                ListItem::Profile(pk) => (profile_path(&pk), short_npub(&pk)),
+                ListItem::Note(id) => {
+                    let note = id.to_bech32().unwrap_or_default();
+                    (
+                        format!("/nostr/{}", note),
+                        format!("{}…", &note[..note.len().min(16)]),
+                    )
+                }
+                ListItem::Address(coordinate) => {
+                    let naddr = coordinate.to_bech32().unwrap_or_default();
+                    (format!("/nostr/{}", naddr), coordinate.identifier.clone())
+                }
+                ListItem::Hashtag(tag) => (format!("/t/{}", tag), format!("#{}", tag)),
+                ListItem::Link(url) => (url.clone(), url),
+                ListItem::Relay(url) => {
+                    return view! { <li class="break-all">{url}</li> };
+                }
+            };
+            view! { <li><a href=href class="text-blue-500 break-all">{text}</a></li> }
+        })
+        .collect_view();
+    view! {
+        <div class=CARD_CLASS>
+            <h1 class="text-2xl font-bold">{address_title(&event)}</h1>
+            <Byline event=event.clone()/>
+            {tag_value(&event, "description").map(|text| view! { <p class="text-sm">{text}</p> })}
+            <ul class="list-disc pl-6 text-sm">{items}</ul>
+        </div>
+    }
+}
+
+/// Kinds we do not draw: NIP-31 `alt` text, then the content as a note.
+#[component]
+pub fn AddressFallback(event: Event) -> impl IntoView {
+    let title = address_title(&event);
+    view! {
+        <div class=CARD_CLASS>
+            <div class="flex items-center justify-between">
+                <h1 class="text-xl font-bold">{title}</h1>
+                <span class="text-xs text-gray-400">{format!("kind {}", event.kind.as_u16())}</span>
+            </div>
+            <Byline event=event.clone()/>
+            {tag_value(&event, "alt").map(|alt| view! { <p class="text-sm italic">{alt}</p> })}
+            <p class="text-sm break-words">{transform_text_to_html(event.content())}</p>
+        </div>
+    }
+}
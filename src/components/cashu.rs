@@ -0,0 +1,93 @@
+use leptos::*;
+
+use crate::components::copy::CopyField;
+use crate::helper::cashu::{check_states, CashuToken, ProofStates};
+
+#[derive(Clone, PartialEq)]
+enum SpentCheck {
+    Idle,
+    Checking,
+    Done(ProofStates),
+    Failed(String),
+}
+
+/// A Cashu token in note content: mint, unit, total and proof count, with an
+/// on demand NUT-07 check of whether it was already claimed.
+#[component]
+pub fn CashuCard(token: String) -> impl IntoView {
+    let decoded = match CashuToken::decode(&token) {
+        Ok(decoded) => decoded,
+        Err(_) => return view! { <span class="cashu break-all">{token}</span> }.into_view(),
+    };
+    let Some(total) = decoded.amount() else {
+        return view! {
+            <span class="cashu block my-1 p-3 rounded-lg border border-red-400 text-sm text-red-500">
+                "โทเคน Cashu นี้มียอดรวมเกินกว่าที่แสดงได้"
+            </span>
+        }
+        .into_view();
+    };
+    let amount = format!("🥜 {} {}", total, decoded.unit);
+    let proofs = format!("{} proofs", decoded.proof_count());
+    let mints = decoded
+        .mints
+        .iter()
+        .map(|mint| view! { <span class="block break-all">"mint: " {mint.mint.clone()}</span> })
+        .collect_view();
+    let memo = decoded.memo.clone();
+    let check = create_rw_signal(SpentCheck::Idle);
+    let run_check = move |_| {
+        check.set(SpentCheck::Checking);
+        let token = decoded.clone();
+        spawn_local(async move {
+            let mut total = ProofStates::default();
+            for mint in &token.mints {
+                match check_states(&mint.mint, &mint.proofs).await {
+                    Ok(states) => {
+                        total.unspent += states.unspent;
+                        total.pending += states.pending;
+                        total.spent += states.spent;
+                    }
+                    Err(e) => {
+                        let _ = check.try_set(SpentCheck::Failed(e));
+                        return;
+                    }
+                }
+            }
+            let _ = check.try_set(SpentCheck::Done(total));
+        });
+    };
+    view! {
+        <span class="cashu block my-1 p-3 rounded-lg border border-orange-400 bg-orange-50 dark:bg-gray-700 text-sm space-y-1">
+            <span class="flex items-center justify-between">
+                <span class="font-bold text-lg">{amount}</span>
+                <span class="text-xs text-gray-500">{proofs}</span>
+            </span>
+            {memo.map(|memo| view! { <span class="block">{memo}</span> })}
+            <span class="block text-xs text-gray-500">{mints}</span>
+            <span class="flex items-center space-x-2 text-xs">
+                {move || match check.get() {
+                    SpentCheck::Idle => view! {
+                        <button type="button" class="underline" on:click=run_check.clone()>
+                            "ตรวจสอบว่าถูกรับไปแล้วหรือยัง"
+                        </button>
+                    }
+                    .into_view(),
+                    SpentCheck::Checking => view! { <span>"กำลังตรวจสอบกับ mint..."</span> }.into_view(),
+                    SpentCheck::Done(states) if states.spent + states.pending == 0 => {
+                        view! { <span class="text-green-600">"ยังไม่ถูกรับ"</span> }.into_view()
+                    }
+                    SpentCheck::Done(states) => view! {
+                        <span class="text-red-500">
+                            {format!("ถูกรับไปแล้ว {}/{}", states.spent + states.pending, states.spent + states.pending + states.unspent)}
+                        </span>
+                    }
+                    .into_view(),
+                    SpentCheck::Failed(e) => view! { <span class="text-red-500">{e}</span> }.into_view(),
+                }}
+            </span>
+            <CopyField text=token.clone()/>
+        </span>
+    }
+    .into_view()
+}
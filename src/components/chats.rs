@@ -1,15 +1,26 @@
 use leptos::{html::Span, *};
+use nostr_sdk::prelude::*;
+
+use crate::components::note_actions::NoteActions;
+use crate::helper::profiles::{profile_path, short_npub, ProfileCache};
+use crate::helper::publish::PublishState;
 
 #[component]
-pub fn ChatOther(ext_contents: Vec<HtmlElement<Span>>, profile: String) -> impl IntoView {
+pub fn ChatOther(
+    ext_contents: Vec<HtmlElement<Span>>,
+    profile: String,
+    #[prop(optional)] note: Option<Event>,
+) -> impl IntoView {
     view! {
         <div class="flex items-end">
             <div class="flex flex-col space-y-2 text-xs max-w-xs mx-2 order-2 items-start">
+                <AuthorName profile=profile.clone()/>
                 <div>
                     <span class="px-4 py-2 rounded-lg inline-block rounded-bl-none bg-gray-300 text-gray-600">
                         {ext_contents}
                     </span>
                 </div>
+                {note.map(|note| view! { <NoteActions note=note/> })}
             </div>
             <Profiles profile=profile order=1/>
         </div>
@@ -17,7 +28,12 @@ pub fn ChatOther(ext_contents: Vec<HtmlElement<Span>>, profile: String) -> impl
 }
 
 #[component]
-pub fn ChatSelf(ext_contents: Vec<HtmlElement<Span>>, profile: String) -> impl IntoView {
+pub fn ChatSelf(
+    ext_contents: Vec<HtmlElement<Span>>,
+    profile: String,
+    #[prop(optional, into)] state: Option<Signal<Option<PublishState>>>,
+    #[prop(optional)] note: Option<Event>,
+) -> impl IntoView {
     view! {
         <div class="flex items-end justify-end">
             <div class="flex flex-col space-y-2 text-xs max-w-xs mx-2 order-1 items-end">
@@ -26,27 +42,90 @@ pub fn ChatSelf(ext_contents: Vec<HtmlElement<Span>>, profile: String) -> impl I
                         {ext_contents}
                     </span>
                 </div>
+                {note.map(|note| view! { <NoteActions note=note/> })}
+                {move || match state.and_then(|s| s.get()) {
+                    Some(PublishState::Pending) => {
+                        view! { <span class="text-gray-400">"กำลังส่ง..."</span> }.into_view()
+                    }
+                    Some(PublishState::Failed(e)) => {
+                        view! { <span class="text-red-500" title=e>"ส่งไม่สำเร็จ"</span> }.into_view()
+                    }
+                    None => ().into_view(),
+                }}
             </div>
             <Profiles profile=profile order=2/>
         </div>
     }
 }
 
+#[component]
+pub fn AuthorName(profile: String) -> impl IntoView {
+    let cache = use_context::<ProfileCache>().expect("profile cache init");
+    let public_key = PublicKey::from_hex(&profile).ok();
+    let names = move || {
+        let public_key = public_key?;
+        let fallback = short_npub(&public_key);
+        match cache.get(&public_key) {
+            Some(p) => Some((p.name().unwrap_or(fallback), p.metadata.nip05, public_key)),
+            None => Some((fallback, None, public_key)),
+        }
+    };
+    view! {
+        <div class="text-gray-500 dark:text-gray-400">
+            {move || {
+                names()
+                    .map(|(name, nip05, public_key)| {
+                        view! {
+                            <a href=profile_path(&public_key) class="font-bold">{name}</a>
+                            {nip05.map(|nip05| view! { <span class="ml-1 text-purple-400">{nip05}</span> })}
+                        }
+                    })
+            }}
+        </div>
+    }
+}
+
 #[component]
 pub fn Profiles(profile: String, order: u8) -> impl IntoView {
-    let url = format!(
-        "https://media.nostr.band/thumbs/{}/{}-picture-64",
-        &profile[60..],
-        profile
-    );
+    let cache = use_context::<ProfileCache>().expect("profile cache init");
+    let public_key = PublicKey::from_hex(&profile).ok();
+    let (broken, set_broken) = create_signal(false);
+    let picture = move || {
+        public_key
+            .and_then(|pk| cache.get(&pk))
+            .and_then(|p| p.metadata.picture)
+            .filter(|url| !url.is_empty() && !broken.get())
+    };
+    let initial = move || {
+        public_key
+            .and_then(|pk| cache.get(&pk))
+            .and_then(|p| p.name())
+            .and_then(|name| name.chars().next())
+            .unwrap_or('?')
+            .to_uppercase()
+            .to_string()
+    };
     let class_list = format!("w-6 h-6 rounded-full order-{}", order);
+    let fallback_class = format!(
+        "{} flex items-center justify-center bg-purple-600 text-white text-xs",
+        class_list
+    );
     view! {
         <div>
-            <img
-                src=url
-                alt="My profile"
-                class=class_list
-            />
+            {move || match picture() {
+                Some(url) => {
+                    view! {
+                        <img
+                            src=url
+                            alt="My profile"
+                            class=class_list.clone()
+                            on:error=move |_| set_broken.set(true)
+                        />
+                    }
+                        .into_view()
+                }
+                None => view! { <div class=fallback_class.clone()>{initial}</div> }.into_view(),
+            }}
         </div>
     }
 }
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::publish::{settle, siamstr_note, sign_and_publish, PublishState};
+use crate::helper::signer::LoginMode;
+use crate::helper::store::EventStore;
+
+#[component]
+pub fn Composer(
+    pending: RwSignal<HashMap<EventId, PublishState>>,
+    /// Hashtags of the feed, every note written here carries them
+    tags: Vec<String>,
+) -> impl IntoView {
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let store = use_context::<EventStore>().expect("event store init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let (draft, set_draft) = create_signal(String::new());
+    let tags = store_value(tags);
+
+    let placeholder = move || match login_mode.get() {
+        LoginMode::LoggedOut => "เข้าสู่ระบบ หรือเลือกปลาอานนท์ก่อนส่งข้อความ",
+        LoginMode::Guest => "เกิดอะไรขึ้น??   ส่งข้อความด้วยปลาอานนท์",
+        LoginMode::Extension => "เกิดอะไรขึ้น??",
+    };
+    let send = move || {
+        let content = draft.get_untracked().trim().to_string();
+        if content.is_empty() || !login_mode.get_untracked().can_sign() {
+            return;
+        }
+        set_draft.set(String::new());
+        let client = client.get_untracked();
+        spawn_local(async move {
+            let mut note_id = None;
+            let note = siamstr_note(&content, &tags.get_value());
+            let result = sign_and_publish(&client, note, |event| {
+                note_id = Some(event.id);
+                pending.update(|map| {
+                    map.insert(event.id, PublishState::Pending);
+                });
+                store.insert(event.clone());
+            })
+            .await;
+            match (note_id, result) {
+                (Some(id), result) => {
+                    if let Err(e) = &result {
+                        log!("publish failed: {}", e);
+                    }
+                    pending.update(|map| settle(map, id, result));
+                }
+                (None, result) => {
+                    // signing was refused, give the text back
+                    log!("signing failed: {:?}", result.err());
+                    set_draft.set(content);
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="border-t-2 border-gray-200 px-4 pt-4 mb-2 sm:mb-0 z-1">
+            <div class="relative flex">
+                <input
+                    type="text"
+                    placeholder=placeholder
+                    class="w-full focus:outline-none focus:placeholder-gray-400 text-gray-600 placeholder-gray-600 pl-12 bg-gray-200 rounded-md py-3"
+                    prop:value=draft
+                    on:input=move |ev| set_draft.set(event_target_value(&ev))
+                    on:keydown=move |ev| {
+                        if ev.key() == "Enter" && !ev.shift_key() {
+                            ev.prevent_default();
+                            send();
+                        }
+                    }
+                />
+                <div class="absolute right-0 items-center inset-y-0">
+                    <button
+                        type="button"
+                        class="inline-flex items-center justify-center rounded-full h-10 w-10 transition duration-500 ease-in-out text-gray-500 hover:bg-gray-300 focus:outline-none"
+                    >
+                        <svg
+                            xmlns="http://www.w3.org/2000/svg"
+                            fill="none"
+                            viewBox="0 0 24 24"
+                            stroke="currentColor"
+                            class="h-6 w-6 text-gray-600"
+                        >
+                            <path
+                                stroke-linecap="round"
+                                stroke-linejoin="round"
+                                stroke-width="2"
+                                d="M3 9a2 2 0 012-2h.93a2 2 0 001.664-.89l.812-1.22A2 2 0 0110.07 4h3.86a2 2 0 011.664.89l.812 1.22A2 2 0 0018.07 7H19a2 2 0 012 2v9a2 2 0 01-2 2H5a2 2 0 01-2-2V9z"
+                            ></path>
+                            <path
+                                stroke-linecap="round"
+                                stroke-linejoin="round"
+                                stroke-width="2"
+                                d="M15 13a3 3 0 11-6 0 3 3 0 016 0z"
+                            ></path>
+                        </svg>
+                    </button>
+                    <button
+                        type="button"
+                        class="inline-flex items-center justify-center rounded-full h-10 w-10 transition duration-500 ease-in-out text-gray-500 hover:bg-gray-300 focus:outline-none"
+                    >
+                        <svg
+                            xmlns="http://www.w3.org/2000/svg"
+                            fill="none"
+                            viewBox="0 0 24 24"
+                            stroke="currentColor"
+                            class="h-6 w-6 text-gray-600"
+                        >
+                            <path
+                                stroke-linecap="round"
+                                stroke-linejoin="round"
+                                stroke-width="2"
+                                d="M14.828 14.828a4 4 0 01-5.656 0M9 10h.01M15 10h.01M21 12a9 9 0 11-18 0 9 9 0 0118 0z"
+                            ></path>
+                        </svg>
+                    </button>
+                    <button
+                        type="button"
+                        class="inline-flex items-center justify-center rounded-lg px-4 py-3 transition duration-500 ease-in-out text-white bg-blue-500 hover:bg-blue-400 focus:outline-none disabled:opacity-50"
+                        disabled=move || !login_mode.get().can_sign()
+                        on:click=move |_| send()
+                    >
+                        <span class="font-bold">"Send"</span>
+                        <svg
+                            xmlns="http://www.w3.org/2000/svg"
+                            viewBox="0 0 20 20"
+                            fill="currentColor"
+                            class="h-6 w-6 ml-2 transform rotate-90"
+                        >
+                            <path d="M10.894 2.553a1 1 0 00-1.788 0l-7 14a1 1 0 001.169 1.409l5-1.429A1 1 0 009 15.571V11a1 1 0 112 0v4.571a1 1 0 00.725.962l5 1.428a1 1 0 001.17-1.408l-7-14z"></path>
+                        </svg>
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
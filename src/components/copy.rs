@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use leptos::html::Input;
+use leptos::wasm_bindgen::JsCast;
+use leptos::*;
+
+/// Read-only field holding `text` with a button that copies it.
+#[component]
+pub fn CopyField(text: String) -> impl IntoView {
+    let input = create_node_ref::<Input>();
+    let (copied, set_copied) = create_signal(false);
+    let copy = move |_| {
+        let Some(input) = input.get_untracked() else {
+            return;
+        };
+        input.select();
+        let done = document()
+            .dyn_into::<web_sys::HtmlDocument>()
+            .ok()
+            .and_then(|doc| doc.exec_command("copy").ok())
+            .unwrap_or(false);
+        if done {
+            set_copied.set(true);
+            set_timeout(
+                move || {
+                    let _ = set_copied.try_set(false);
+                },
+                Duration::from_secs(2),
+            );
+        }
+    };
+    view! {
+        <div class="flex w-full space-x-1">
+            <input
+                type="text"
+                readonly
+                class="flex-1 min-w-0 rounded px-2 py-1 border text-xs"
+                value=text
+                node_ref=input
+                on:focus=move |ev| {
+                    event_target::<web_sys::HtmlInputElement>(&ev).select();
+                }
+            />
+            <button
+                type="button"
+                class="rounded px-2 text-xs bg-purple-600 text-white hover:bg-purple-700"
+                on:click=copy
+            >
+                {move || if copied.get() { "คัดลอกแล้ว" } else { "คัดลอก" }}
+            </button>
+        </div>
+    }
+}
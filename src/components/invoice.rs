@@ -0,0 +1,66 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::copy::CopyField;
+use crate::components::qr::QrCode;
+use crate::helper::bolt11::Invoice;
+
+fn short_hex(hex: &str) -> String {
+    format!(
+        "{}…{}",
+        &hex[..hex.len().min(8)],
+        &hex[hex.len().saturating_sub(8)..]
+    )
+}
+
+/// A BOLT11 invoice in note content: amount, description, payee and expiry,
+/// with a QR and a wallet link while it can still be paid.
+#[component]
+pub fn InvoiceCard(invoice: String) -> impl IntoView {
+    let decoded = match Invoice::decode(&invoice) {
+        Ok(decoded) => decoded,
+        Err(_) => return view! { <span class="invoice break-all">{invoice}</span> }.into_view(),
+    };
+    let expired = decoded.is_expired(Timestamp::now());
+    let amount = match decoded.amount_msat {
+        Some(msat) => format!("⚡ {} sats", msat / 1000),
+        None => "⚡ ระบุจำนวนเอง".to_string(),
+    };
+    let expires_at = decoded.expires_at().to_human_datetime();
+    let (show_qr, set_show_qr) = create_signal(false);
+    let href = format!("lightning:{}", invoice);
+    let qr = invoice.to_uppercase();
+    view! {
+        <span class="invoice block my-1 p-3 rounded-lg border border-amber-400 bg-amber-50 dark:bg-gray-700 text-sm space-y-1">
+            <span class="flex items-center justify-between">
+                <span class="font-bold text-lg">{amount}</span>
+                {decoded.network.label().map(|label| view! { <span class="text-xs text-gray-500">{label}</span> })}
+                {expired.then(|| view! { <span class="rounded-full px-2 bg-gray-400 text-white text-xs">"หมดอายุแล้ว"</span> })}
+            </span>
+            {decoded.description.map(|text| view! { <span class="block">{text}</span> })}
+            <span class="block text-xs text-gray-500">
+                {decoded.payee.map(|payee| view! { <span class="block">"ผู้รับ: " {short_hex(&payee)}</span> })}
+                <span class="block">"หมดอายุ: " {expires_at}</span>
+                {decoded.payment_hash.map(|hash| view! { <span class="block">"payment hash: " {short_hex(&hash)}</span> })}
+            </span>
+            {(!expired)
+                .then(|| {
+                    view! {
+                        <span class="flex items-center space-x-2">
+                            <a href=href class="rounded-full px-3 py-1 bg-amber-400 text-white">
+                                "เปิดกระเป๋า Lightning"
+                            </a>
+                            <button type="button" class="text-xs underline" on:click=move |_| set_show_qr.update(|show| *show = !*show)>
+                                "QR"
+                            </button>
+                        </span>
+                        <Show when=move || show_qr.get()>
+                            <QrCode data=qr.clone()/>
+                        </Show>
+                    }
+                })}
+            <CopyField text=invoice.clone()/>
+        </span>
+    }
+    .into_view()
+}
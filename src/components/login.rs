@@ -0,0 +1,94 @@
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::profiles::short_npub;
+use crate::helper::signer::{has_extension, logout, use_extension, use_guest, LoginMode};
+
+#[component]
+pub fn LoginBar() -> impl IntoView {
+    let app_keys = use_context::<RwSignal<Keys>>().expect("app key init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let (error, set_error) = create_signal(None::<String>);
+
+    let on_extension = move |_| {
+        let client = client.get_untracked();
+        spawn_local(async move {
+            match use_extension(&client).await {
+                Ok(keys) => {
+                    app_keys.set(keys);
+                    login_mode.set(LoginMode::Extension);
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    log!("NIP-07 login failed: {}", e);
+                    set_error.set(Some(e));
+                }
+            }
+        });
+    };
+    let on_guest = move |_| {
+        let client = client.get_untracked();
+        spawn_local(async move {
+            app_keys.set(use_guest(&client).await);
+            login_mode.set(LoginMode::Guest);
+        });
+    };
+    let on_logout = move |_| {
+        let client = client.get_untracked();
+        spawn_local(async move {
+            app_keys.set(logout(&client).await);
+            login_mode.set(LoginMode::LoggedOut);
+        });
+    };
+
+    view! {
+        <div class="flex items-center space-x-2 text-xs">
+            {move || match login_mode.get() {
+                LoginMode::LoggedOut => {
+                    view! {
+                        <button
+                            type="button"
+                            class="rounded-lg px-3 py-1 text-white bg-purple-600 hover:bg-purple-500 disabled:opacity-50"
+                            disabled=!has_extension()
+                            title="NIP-07 (Alby, nos2x, ...)"
+                            on:click=on_extension
+                        >
+                            "เข้าสู่ระบบ"
+                        </button>
+                        <button
+                            type="button"
+                            class="rounded-lg px-3 py-1 text-gray-600 bg-gray-200 hover:bg-gray-300"
+                            on:click=on_guest
+                        >
+                            "ปลาอานนท์"
+                        </button>
+                    }
+                        .into_view()
+                }
+                mode => {
+                    let public_key = app_keys.with(|keys| keys.public_key());
+                    let npub = public_key.to_bech32().unwrap_or_default();
+                    let label = if mode == LoginMode::Guest {
+                        "ปลาอานนท์".to_string()
+                    } else {
+                        short_npub(&public_key)
+                    };
+                    view! {
+                        <span class="text-gray-600 dark:text-purple-200" title=npub>{label}</span>
+                        <button
+                            type="button"
+                            class="rounded-lg px-3 py-1 text-gray-600 bg-gray-200 hover:bg-gray-300"
+                            on:click=on_logout
+                        >
+                            "ออก"
+                        </button>
+                    }
+                        .into_view()
+                }
+            }}
+            {move || error.get().map(|e| view! { <span class="text-red-500">{e}</span> })}
+        </div>
+    }
+}
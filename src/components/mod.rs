@@ -1,2 +1,16 @@
+pub mod address;
+pub mod cashu;
 pub mod chats;
+pub mod composer;
+pub mod copy;
+pub mod invoice;
 pub mod loading;
+pub mod login;
+pub mod nostr_ref;
+pub mod note_actions;
+pub mod note_content;
+pub mod notifications;
+pub mod qr;
+pub mod relay_status;
+pub mod share;
+pub mod zap;
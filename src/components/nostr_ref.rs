@@ -0,0 +1,167 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::{AuthorName, Profiles};
+use crate::components::note_content::{article_preview, transform_text_at_depth};
+use crate::helper::address::{address_identifier, address_title, tag_value, AddressKind};
+use crate::helper::mutes::Mutes;
+use crate::helper::outbox::Outbox;
+use crate::helper::profiles::{profile_path, short_npub, ProfileCache};
+use crate::helper::store::EventStore;
+
+/// Quotes inside quotes stop here and fall back to a plain link.
+pub const MAX_QUOTE_DEPTH: usize = 2;
+
+const QUOTE_CLASS: &str =
+    "block my-1 p-2 border-l-4 border-purple-400 rounded bg-gray-100 dark:bg-gray-700 text-sm";
+
+fn short_entity(entity: &str) -> String {
+    format!("{}…", &entity[..entity.len().min(16)])
+}
+
+/// A `nostr:` reference in note content, drawn from the shared store and
+/// profile cache. `depth` counts the quotes around it.
+#[component]
+pub fn NostrRef(entity: String, depth: usize) -> impl IntoView {
+    let href = format!("/nostr/{}", entity);
+    match Nip19::from_bech32(&entity) {
+        Ok(Nip19::Pubkey(public_key)) => view! { <ProfileChip public_key=public_key/> }.into_view(),
+        Ok(Nip19::Profile(profile)) => {
+            view! { <ProfileChip public_key=profile.public_key/> }.into_view()
+        }
+        Ok(Nip19::EventId(id)) if depth < MAX_QUOTE_DEPTH => {
+            view! { <QuoteCard id=id href=href depth=depth/> }.into_view()
+        }
+        Ok(Nip19::Event(event)) if depth < MAX_QUOTE_DEPTH => view! {
+            <QuoteCard id=event.event_id author=event.author href=href depth=depth/>
+        }
+        .into_view(),
+        Ok(Nip19::Coordinate(coordinate)) if depth < MAX_QUOTE_DEPTH => {
+            view! { <AddressPreview coordinate=coordinate href=href/> }.into_view()
+        }
+        _ => view! { <a href=href class="text-blue-500">{short_entity(&entity)}</a> }.into_view(),
+    }
+}
+
+/// `@name` linking to the profile page.
+#[component]
+pub fn ProfileChip(public_key: PublicKey) -> impl IntoView {
+    let profiles = use_context::<ProfileCache>().expect("profile cache init");
+    let name = move || {
+        profiles
+            .get(&public_key)
+            .and_then(|p| p.name())
+            .unwrap_or_else(|| short_npub(&public_key))
+    };
+    view! {
+        <a
+            href=profile_path(&public_key)
+            class="inline-block px-2 rounded-full bg-purple-100 text-purple-700 dark:bg-purple-900 dark:text-purple-200"
+        >
+            "@"
+            {name}
+        </a>
+    }
+}
+
+/// The quoted note with its author, fetched by id when the store lacks it.
+#[component]
+fn QuoteCard(
+    id: EventId,
+    #[prop(default = None)] author: Option<PublicKey>,
+    href: String,
+    depth: usize,
+) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let (missing, set_missing) = create_signal(false);
+    if store.get_untracked(&id).is_none() {
+        spawn_local(async move {
+            let found = outbox.fetch_event(id, author, Vec::new()).await;
+            let _ = set_missing.try_set(found.is_none());
+        });
+    }
+    // the store changes with every event, redraw only when this one does
+    let quoted = create_memo(move |_| store.get(&id));
+    let link = href.clone();
+    move || match quoted.get() {
+        Some(event) if mutes.hides(&event) => {
+            view! { <span class=QUOTE_CLASS>"โน้ตที่ถูกซ่อน"</span> }.into_view()
+        }
+        Some(event) => {
+            let profile = event.pubkey.to_hex();
+            let body = if event.kind == Kind::LongFormTextNote {
+                article_preview(&event)
+            } else {
+                transform_text_at_depth(event.content(), depth + 1)
+            };
+            view! {
+                <span class=QUOTE_CLASS>
+                    <span class="flex items-center space-x-2 text-xs">
+                        <Profiles profile=profile.clone() order=1/>
+                        <AuthorName profile=profile/>
+                        <a href=link.clone() class="text-gray-400">
+                            {event.created_at.to_human_datetime()}
+                        </a>
+                    </span>
+                    <span class="block break-words">{body}</span>
+                </span>
+            }
+            .into_view()
+        }
+        None if missing.get() => {
+            view! { <a href=link.clone() class="text-blue-500">"ไม่พบโน้ตที่อ้างถึง"</a> }.into_view()
+        }
+        None => view! { <span class=QUOTE_CLASS>"กำลังโหลดโน้ต…"</span> }.into_view(),
+    }
+}
+
+/// Title and summary of an `naddr` target, linking to its page.
+#[component]
+fn AddressPreview(coordinate: Coordinate, href: String) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let kind = coordinate.kind;
+    let author = coordinate.public_key;
+    let identifier = address_identifier(&coordinate).map(str::to_string);
+    if store
+        .replaceable_untracked(kind, author, identifier.as_deref())
+        .is_none()
+    {
+        spawn_local(async move {
+            outbox.fetch_address(&coordinate, Vec::new()).await;
+        });
+    }
+    let fallback = identifier.clone().unwrap_or_default();
+    let preview = create_memo(move |_| {
+        store
+            .replaceable(kind, author, identifier.as_deref())
+            .filter(|event| !mutes.hides(event))
+            .map(|event| {
+                let icon = match AddressKind::of(event.kind) {
+                    AddressKind::Article => "📝",
+                    AddressKind::LiveEvent => "🎥",
+                    AddressKind::List => "📋",
+                    AddressKind::Calendar => "📅",
+                    AddressKind::Other => "🔗",
+                };
+                let summary =
+                    tag_value(&event, "summary").or_else(|| tag_value(&event, "description"));
+                (icon, address_title(&event), summary)
+            })
+    });
+    view! {
+        <a href=href class=QUOTE_CLASS>
+            {move || match preview.get() {
+                Some((icon, title, summary)) => view! {
+                    <span class="font-bold">{icon} " " {title}</span>
+                    {summary.map(|summary| view! { <span class="block text-gray-500">{summary}</span> })}
+                }
+                .into_view(),
+                None => view! { <span class="font-bold">"🔗 " {fallback.clone()}</span> }.into_view(),
+            }}
+        </a>
+    }
+}
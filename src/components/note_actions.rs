@@ -0,0 +1,154 @@
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::share::{ShareButton, ShareTarget};
+use crate::components::zap::ZapButton;
+use crate::helper::mutes::{MuteItem, Mutes};
+use crate::helper::publish::sign_and_publish;
+use crate::helper::reactions::{ReactionKey, ReactionStore};
+use crate::helper::signer::LoginMode;
+use crate::helper::thread::thread_refs;
+
+const QUICK_REACTIONS: [&str; 6] = ["+", "🤙", "❤️", "😂", "⚡", "🫡"];
+
+#[component]
+fn ReactionLabel(key: ReactionKey) -> impl IntoView {
+    match key {
+        ReactionKey::Like => "👍".into_view(),
+        ReactionKey::Dislike => "👎".into_view(),
+        ReactionKey::Emoji(emoji) => emoji.into_view(),
+        ReactionKey::Custom { shortcode, url } => {
+            view! { <img src=url alt=shortcode.clone() title=shortcode class="inline w-4 h-4"/> }
+                .into_view()
+        }
+    }
+}
+
+/// Reaction summary, zap tally, the react picker, share and mute actions shown
+/// under a bubble.
+#[component]
+pub fn NoteActions(note: Event) -> impl IntoView {
+    let reactions = use_context::<ReactionStore>().expect("reaction store init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let app_keys = use_context::<RwSignal<Keys>>().expect("app key init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let (picker, set_picker) = create_signal(false);
+    let (mute_menu, set_mute_menu) = create_signal(false);
+    let author = note.pubkey;
+    let thread_root = thread_refs(&note).root.unwrap_or(note.id);
+    let note_id = note.id;
+    let zap_total = Signal::derive(move || reactions.zap_total(note_id));
+    let zap_note = note.clone();
+    let share = ShareTarget::Note(note.clone());
+    let note = store_value(note);
+
+    let mine = move || {
+        if !login_mode.get().can_sign() {
+            return Default::default();
+        }
+        app_keys.with(|keys| reactions.reacted_by(note_id, &keys.public_key()))
+    };
+    let react = move |content: &'static str| {
+        set_picker.set(false);
+        let client = client.get_untracked();
+        let builder = note.with_value(|note| EventBuilder::reaction(note, content));
+        spawn_local(async move {
+            let result = sign_and_publish(&client, builder, |event| reactions.insert(event)).await;
+            if let Err(e) = result {
+                log!("reaction failed: {}", e);
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-wrap items-center gap-1 text-xs">
+            <For
+                each=move || reactions.summary(note_id)
+                key=|entry| entry.clone()
+                children=move |(key, count)| {
+                    let label = key.clone();
+                    let class_list = move || {
+                        if mine().contains(&key) {
+                            "rounded-full px-2 bg-purple-200 text-purple-800"
+                        } else {
+                            "rounded-full px-2 bg-gray-200 text-gray-600"
+                        }
+                    };
+                    view! {
+                        <span class=class_list>
+                            <ReactionLabel key=label/>
+                            " "
+                            {count}
+                        </span>
+                    }
+                }
+            />
+            <ZapButton note=zap_note total=zap_total/>
+            <ShareButton target=share/>
+            <Show when=move || login_mode.get().can_sign()>
+                <button
+                    type="button"
+                    class="rounded-full px-2 text-gray-400 hover:bg-gray-200"
+                    title="React"
+                    on:click=move |_| set_picker.update(|open| *open = !*open)
+                >
+                    "☺"
+                </button>
+                <Show when=move || picker.get()>
+                    <span class="flex space-x-1">
+                        {QUICK_REACTIONS
+                            .into_iter()
+                            .map(|content| {
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="hover:scale-125"
+                                        on:click=move |_| react(content)
+                                    >
+                                        {if content == "+" { "👍" } else { content }}
+                                    </button>
+                                }
+                            })
+                            .collect_view()}
+                    </span>
+                </Show>
+            </Show>
+            <Show when=move || login_mode.get() == LoginMode::Extension && mutes.is_loaded()>
+                <button
+                    type="button"
+                    class="rounded-full px-2 text-gray-400 hover:bg-gray-200"
+                    title="ปิดเสียง"
+                    on:click=move |_| set_mute_menu.update(|open| *open = !*open)
+                >
+                    "🔇"
+                </button>
+                <Show when=move || mute_menu.get()>
+                    <span class="flex space-x-1">
+                        <button
+                            type="button"
+                            class="rounded-full px-2 bg-gray-200 hover:bg-gray-300"
+                            on:click=move |_| {
+                                set_mute_menu.set(false);
+                                mutes.mute(MuteItem::Author(author));
+                            }
+                        >
+                            "ปิดเสียงผู้ใช้"
+                        </button>
+                        <button
+                            type="button"
+                            class="rounded-full px-2 bg-gray-200 hover:bg-gray-300"
+                            on:click=move |_| {
+                                set_mute_menu.set(false);
+                                mutes.mute(MuteItem::Thread(thread_root));
+                            }
+                        >
+                            "ปิดเสียงเธรด"
+                        </button>
+                    </span>
+                </Show>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,67 @@
+use leptos::{html::Span, *};
+use nostr_sdk::prelude::*;
+
+use crate::components::cashu::CashuCard;
+use crate::components::invoice::InvoiceCard;
+use crate::components::nostr_ref::NostrRef;
+use crate::helper::article::Article;
+use crate::helper::note_regex::{tokenize, Token, TokenKind};
+
+/// One token as a span, `depth` is how many quotes it sits in.
+pub fn render_token(content: &str, token: &Token, depth: usize) -> HtmlElement<Span> {
+    let text = token.text(content).to_owned();
+    match &token.kind {
+        TokenKind::Text => view! { <span>{text}</span> },
+        TokenKind::Hashtag(tag) => {
+            let url = format!("/t/{}", tag.to_lowercase());
+            view! { <span class="hashtag"><a href=url>{text}</a></span> }
+        }
+        TokenKind::Url => {
+            view! { <span class="link"><a href=text.clone() target="_blank">{text}</a></span> }
+        }
+        TokenKind::Image => view! { <span class="img"><img src=text/></span> },
+        TokenKind::Video => {
+            view! { <span class="video"><video controls><source src=text/></video></span> }
+        }
+        TokenKind::YouTube(id) => {
+            let url = format!("https://youtube.com/embed/{}", id);
+            view! { <span class="video"><iframe src=url title="YouTube video player" frameborder="0" allowfullscreen></iframe></span> }
+        }
+        TokenKind::Invoice => view! { <span><InvoiceCard invoice=text/></span> },
+        TokenKind::Cashu => view! { <span><CashuCard token=text/></span> },
+        TokenKind::NostrRef(entity) => {
+            view! { <span class="nostr-entity"><NostrRef entity=entity.clone() depth=depth/></span> }
+        }
+        TokenKind::CodeBlock(code) => {
+            view! { <span class="markdown"><pre><code>{code.to_owned()}</code></pre></span> }
+        }
+        TokenKind::LineBreak => view! { <span><br/></span> },
+    }
+}
+
+pub fn transform_text_to_html(text: &str) -> Vec<HtmlElement<Span>> {
+    transform_text_at_depth(text, 0)
+}
+
+/// Content of a note quoted `depth` levels deep.
+pub fn transform_text_at_depth(text: &str, depth: usize) -> Vec<HtmlElement<Span>> {
+    tokenize(text)
+        .iter()
+        .map(|token| render_token(text, token, depth))
+        .collect()
+}
+
+/// Bubble body for a kind-30023: title and summary linking to the article page.
+pub fn article_preview(event: &Event) -> Vec<HtmlElement<Span>> {
+    let Some(article) = Article::from_event(event) else {
+        return transform_text_to_html(event.content());
+    };
+    let url = format!("/nostr/{}", article.naddr(event.pubkey));
+    let title = article.title.unwrap_or(article.identifier);
+    vec![view! {
+        <span class="article">
+            <a href=url class="font-bold underline">{"📝 "}{title}</a>
+            {article.summary.map(|summary| view! { <span class="block">{summary}</span> })}
+        </span>
+    }]
+}
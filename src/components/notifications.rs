@@ -0,0 +1,61 @@
+use leptos::*;
+use leptos_router::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::notifications::Notifier;
+use crate::helper::signer::LoginMode;
+
+/// Keeps the notification subscription in line with the login and the opt-in.
+/// Lives inside the router so a clicked notification can navigate.
+#[component]
+pub fn NotificationListener() -> impl IntoView {
+    let notifier = use_context::<Notifier>().expect("notifier init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let app_keys = use_context::<RwSignal<Keys>>().expect("app key init");
+    let navigate = use_navigate();
+
+    // the guest key is shared, only a personal key gets notifications
+    create_effect(move |_| {
+        if notifier.is_enabled() && login_mode.get() == LoginMode::Extension {
+            let me = app_keys.with(|keys| keys.public_key());
+            let navigate = navigate.clone();
+            notifier.start(me, move |path| navigate(path, Default::default()));
+        } else {
+            notifier.stop();
+        }
+    });
+    on_cleanup(move || notifier.stop());
+}
+
+/// Opt-in switch for the settings page.
+#[component]
+pub fn NotificationToggle() -> impl IntoView {
+    let notifier = use_context::<Notifier>().expect("notifier init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let (denied, set_denied) = create_signal(false);
+    view! {
+        <label class="flex items-center space-x-2 text-sm text-gray-600 dark:text-gray-300">
+            <input
+                type="checkbox"
+                prop:checked=move || notifier.is_enabled()
+                on:change=move |ev| {
+                    if event_target_checked(&ev) {
+                        set_denied.set(false);
+                        notifier.enable(move || {
+                            let _ = set_denied.try_set(true);
+                        });
+                    } else {
+                        notifier.disable();
+                    }
+                }
+            />
+            <span>"แจ้งเตือนเมื่อมีคนตอบกลับ กล่าวถึง รีแอค หรือ Zap (เมื่อไม่ได้เปิดแท็บนี้อยู่)"</span>
+        </label>
+        <Show when=move || login_mode.get() != LoginMode::Extension>
+            <p class="text-xs text-gray-400 mt-1">"ใช้ได้เมื่อเข้าสู่ระบบด้วย extension"</p>
+        </Show>
+        <Show when=move || denied.get()>
+            <p class="text-red-500 text-xs mt-1">"เบราว์เซอร์ไม่อนุญาตให้แจ้งเตือน"</p>
+        </Show>
+    }
+}
@@ -0,0 +1,10 @@
+use leptos::*;
+use qrcode_generator::QrCodeEcc;
+
+/// `data` as an inline SVG QR code.
+#[component]
+pub fn QrCode(data: String, #[prop(default = 256)] size: usize) -> impl IntoView {
+    let svg = qrcode_generator::to_svg_to_string(&data, QrCodeEcc::Low, size, None::<&str>)
+        .unwrap_or_default();
+    view! { <div class="bg-white p-2 rounded-lg" inner_html=svg></div> }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::relay_status::RelayMonitor;
+
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+
+fn status_badge(status: Option<RelayStatus>) -> (&'static str, &'static str) {
+    match status {
+        Some(RelayStatus::Connected) => ("เชื่อมต่อแล้ว", "text-green-600"),
+        Some(RelayStatus::Connecting) | Some(RelayStatus::Pending) => {
+            ("กำลังเชื่อมต่อ", "text-yellow-600")
+        }
+        Some(RelayStatus::Disconnected) => ("หลุด รอเชื่อมใหม่", "text-red-500"),
+        Some(RelayStatus::Stopped) | Some(RelayStatus::Terminated) => ("หยุด", "text-gray-400"),
+        Some(RelayStatus::Initialized) | None => ("ยังไม่เชื่อมต่อ", "text-gray-400"),
+    }
+}
+
+/// Live state of every relay in the pool, polled while mounted.
+#[component]
+pub fn RelayStatusPanel() -> impl IntoView {
+    let monitor = use_context::<RelayMonitor>().expect("relay monitor init");
+    monitor.refresh();
+    if let Ok(handle) = set_interval_with_handle(move || monitor.refresh(), REFRESH_EVERY) {
+        on_cleanup(move || handle.clear());
+    }
+
+    view! {
+        <div class="flex flex-col space-y-3 mb-6">
+            <For
+                each=move || monitor.urls()
+                key=|url| url.clone()
+                children=move |url| {
+                    let row_url = url.clone();
+                    let health = create_memo(move |_| monitor.health(&row_url));
+                    let badge = move || status_badge(health.with(|h| h.status));
+                    let latency = move || {
+                        health
+                            .with(|h| h.latency)
+                            .map(|l| format!("{} ms", l.as_millis()))
+                            .unwrap_or_else(|| "-".to_string())
+                    };
+                    view! {
+                        <div class="rounded-lg border border-gray-200 dark:border-gray-700 p-3 text-sm text-gray-600 dark:text-gray-300">
+                            <div class="flex justify-between">
+                                <span class="font-bold break-all">{url.to_string()}</span>
+                                <span class=move || badge().1>{move || badge().0}</span>
+                            </div>
+                            <div class="flex flex-wrap gap-x-4 text-xs mt-1">
+                                <span>
+                                    {move || {
+                                        health
+                                            .with(|h| {
+                                                format!("พยายามเชื่อมต่อ {} ครั้ง สำเร็จ {}", h.attempts, h.successes)
+                                            })
+                                    }}
+                                </span>
+                                <span>{move || format!("latency {}", latency())}</span>
+                                <span>{move || format!("events {}", health.with(|h| h.events))}</span>
+                            </div>
+                            {move || {
+                                health
+                                    .with(|h| h.last_error.clone())
+                                    .map(|e| view! { <p class="text-xs text-red-500 mt-1">{e}</p> })
+                            }}
+                            <ul class="text-xs text-gray-400 mt-1 max-h-24 overflow-y-auto">
+                                {move || {
+                                    health
+                                        .with(|h| h.messages.clone())
+                                        .into_iter()
+                                        .map(|line| view! { <li class="break-all">{line}</li> })
+                                        .collect_view()
+                                }}
+                            </ul>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,96 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::copy::CopyField;
+use crate::components::qr::QrCode;
+use crate::helper::outbox::RelayList;
+use crate::helper::relays::RelayConfig;
+use crate::helper::share::{share_hints, share_note, share_profile, ShareLinks};
+use crate::helper::store::EventStore;
+
+/// What a share button hands over.
+#[derive(Clone)]
+pub enum ShareTarget {
+    Note(Event),
+    Profile(PublicKey),
+}
+
+/// Toggles a panel with the `nevent`/`nprofile`, `nostr:` URI and site URL,
+/// each copyable, and a QR of the chosen one.
+#[component]
+pub fn ShareButton(target: ShareTarget) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let relays = use_context::<RwSignal<Vec<RelayConfig>>>().expect("relays init");
+    let (open, set_open) = create_signal(false);
+    let target = store_value(target);
+    // built when opened, the author's relay list may have arrived by then
+    let links = move || {
+        let author = target.with_value(|target| match target {
+            ShareTarget::Note(event) => event.pubkey,
+            ShareTarget::Profile(public_key) => *public_key,
+        });
+        let author_relays = store
+            .replaceable_untracked(Kind::RelayList, author, None)
+            .map(|event| RelayList::from_event(&event));
+        let own: Vec<String> = relays.with_untracked(|relays| {
+            relays
+                .iter()
+                .filter(|relay| relay.write)
+                .map(|relay| relay.url.clone())
+                .collect()
+        });
+        let hints = share_hints(author_relays, &own);
+        let origin = window().location().origin().unwrap_or_default();
+        target.with_value(|target| match target {
+            ShareTarget::Note(event) => share_note(event, hints, &origin),
+            ShareTarget::Profile(public_key) => share_profile(*public_key, hints, &origin),
+        })
+    };
+    view! {
+        <button
+            type="button"
+            class="rounded-full px-2 text-gray-400 hover:bg-gray-200"
+            title="แชร์"
+            on:click=move |_| set_open.update(|open| *open = !*open)
+        >
+            "🔗"
+        </button>
+        <Show when=move || open.get()>
+            <SharePanel links=links()/>
+        </Show>
+    }
+}
+
+#[component]
+fn SharePanel(links: ShareLinks) -> impl IntoView {
+    // QR of the site link by default, it opens on phones without a nostr app
+    let (use_uri, set_use_uri) = create_signal(false);
+    let qr_web = links.web.clone();
+    let qr_uri = links.uri.clone();
+    let tab = move |uri: bool| {
+        if use_uri.get() == uri {
+            "rounded-full px-2 bg-purple-600 text-white"
+        } else {
+            "rounded-full px-2 bg-gray-200 text-gray-600"
+        }
+    };
+    view! {
+        <div class="flex flex-col items-center w-full max-w-xs space-y-2 p-2 rounded-lg border border-gray-200 bg-white dark:bg-gray-800">
+            <div class="flex space-x-1 text-xs">
+                <button type="button" class=move || tab(false) on:click=move |_| set_use_uri.set(false)>
+                    "ลิงก์เว็บ"
+                </button>
+                <button type="button" class=move || tab(true) on:click=move |_| set_use_uri.set(true)>
+                    "nostr:"
+                </button>
+            </div>
+            {move || {
+                let data = if use_uri.get() { qr_uri.clone() } else { qr_web.clone() };
+                view! { <QrCode data=data size=192/> }
+            }}
+            <CopyField text=links.web/>
+            <CopyField text=links.uri/>
+            <CopyField text=links.entity/>
+        </div>
+    }
+}
@@ -0,0 +1,178 @@
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::qr::QrCode;
+use crate::helper::profiles::ProfileCache;
+use crate::helper::relays::RelayConfig;
+use crate::helper::signer::LoginMode;
+use crate::helper::zap::{
+    encode_lnurl, fetch_invoice, fetch_pay_params, lnurl_pay_url, zap_request,
+};
+
+const ZAP_AMOUNTS: [u64; 5] = [21, 100, 500, 1_000, 5_000];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ZapState {
+    Idle,
+    Fetching,
+    Invoice(String),
+    Failed(String),
+}
+
+/// Sign a zap request for `note` and get an invoice from the author's wallet.
+async fn request_invoice(
+    client: &Client,
+    note: &Event,
+    lnurl_url: &str,
+    relays: Vec<String>,
+    sats: u64,
+    comment: &str,
+) -> Result<String, String> {
+    let amount_msat = sats * 1000;
+    let pay = fetch_pay_params(lnurl_url).await?;
+    let lnurl = encode_lnurl(lnurl_url);
+    let builder = zap_request(
+        note.pubkey,
+        Some(note.id),
+        relays,
+        amount_msat,
+        lnurl.clone(),
+        comment,
+    );
+    let request = client
+        .sign_event_builder(builder)
+        .await
+        .map_err(|e| e.to_string())?;
+    fetch_invoice(&pay, amount_msat, &request, lnurl.as_deref()).await
+}
+
+/// ⚡ button with the note's zap tally, opens an amount picker and the invoice QR.
+#[component]
+pub fn ZapButton(note: Event, total: Signal<(usize, u64)>) -> impl IntoView {
+    let cache = use_context::<ProfileCache>().expect("profile cache init");
+    let relays = use_context::<RwSignal<Vec<RelayConfig>>>().expect("relays init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    let (open, set_open) = create_signal(false);
+    let (comment, set_comment) = create_signal(String::new());
+    let state = create_rw_signal(ZapState::Idle);
+    let author = note.pubkey;
+    let note = store_value(note);
+
+    let lnurl_url = move || {
+        cache
+            .get(&author)
+            .and_then(|profile| lnurl_pay_url(&profile.metadata))
+    };
+    let zap = move |sats: u64| {
+        let Some(url) = lnurl_url() else {
+            return;
+        };
+        state.set(ZapState::Fetching);
+        let client = client.get_untracked();
+        let note = note.get_value();
+        let comment = comment.get_untracked();
+        let relays = relays.with_untracked(|relays| {
+            relays
+                .iter()
+                .filter(|r| r.read)
+                .map(|r| r.url.clone())
+                .collect()
+        });
+        spawn_local(async move {
+            match request_invoice(&client, &note, &url, relays, sats, &comment).await {
+                Ok(invoice) => state.set(ZapState::Invoice(invoice)),
+                Err(e) => {
+                    log!("zap failed: {}", e);
+                    state.set(ZapState::Failed(e));
+                }
+            }
+        });
+    };
+    let close = move || {
+        set_open.set(false);
+        state.set(ZapState::Idle);
+    };
+
+    view! {
+        <button
+            type="button"
+            class="rounded-full px-2 text-amber-500 hover:bg-gray-200 disabled:text-gray-300"
+            title=move || match (login_mode.get().can_sign(), lnurl_url().is_some()) {
+                (false, _) => "เข้าสู่ระบบก่อน Zap",
+                (true, false) => "ผู้เขียนยังไม่ได้ตั้ง Lightning address",
+                (true, true) => "Zap",
+            }
+            disabled=move || !login_mode.get().can_sign() || lnurl_url().is_none()
+            on:click=move |_| set_open.update(|o| *o = !*o)
+        >
+            "⚡"
+            {move || {
+                let (count, sats) = total.get();
+                (count > 0).then(|| format!(" {} sats", sats))
+            }}
+        </button>
+        <Show when=move || open.get()>
+            <div class="flex flex-col items-center space-y-2 w-full rounded-lg border border-amber-300 bg-amber-50 p-2 text-gray-700">
+                {move || match state.get() {
+                    ZapState::Idle | ZapState::Failed(_) => {
+                        view! {
+                            <input
+                                type="text"
+                                class="w-full rounded px-2 py-1 border"
+                                placeholder="ข้อความ (ไม่บังคับ)"
+                                prop:value=comment
+                                on:input=move |ev| set_comment.set(event_target_value(&ev))
+                            />
+                            <div class="flex flex-wrap gap-1">
+                                {ZAP_AMOUNTS
+                                    .into_iter()
+                                    .map(|sats| {
+                                        view! {
+                                            <button
+                                                type="button"
+                                                class="rounded-full px-2 bg-amber-400 text-white hover:bg-amber-500"
+                                                on:click=move |_| zap(sats)
+                                            >
+                                                {format!("⚡{}", sats)}
+                                            </button>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                            {move || match state.get() {
+                                ZapState::Failed(e) => view! { <span class="text-red-500">{e}</span> }.into_view(),
+                                _ => ().into_view(),
+                            }}
+                        }
+                            .into_view()
+                    }
+                    ZapState::Fetching => view! { <span>"กำลังขอ invoice..."</span> }.into_view(),
+                    ZapState::Invoice(invoice) => {
+                        let href = format!("lightning:{}", invoice);
+                        view! {
+                            <QrCode data=invoice.to_uppercase()/>
+                            <a href=href class="rounded-full px-3 py-1 bg-amber-400 text-white">
+                                "เปิดกระเป๋า Lightning"
+                            </a>
+                            <input
+                                type="text"
+                                readonly
+                                class="w-full rounded px-2 py-1 border text-xs"
+                                value=invoice
+                                on:focus=move |ev| {
+                                    event_target::<web_sys::HtmlInputElement>(&ev).select();
+                                }
+                            />
+                        }
+                            .into_view()
+                    }
+                }}
+                <button type="button" class="text-gray-400" on:click=move |_| close()>
+                    "ปิด"
+                </button>
+            </div>
+        </Show>
+    }
+}
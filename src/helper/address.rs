@@ -0,0 +1,240 @@
+use nostr_sdk::prelude::*;
+
+use crate::helper::profiles::website_url;
+
+/// How an `naddr` target is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressKind {
+    Article,
+    /// NIP-53 live stream
+    LiveEvent,
+    /// NIP-51 list or set
+    List,
+    /// NIP-52 date (31922) or time (31923) based event
+    Calendar,
+    Other,
+}
+
+impl AddressKind {
+    pub fn of(kind: Kind) -> Self {
+        match kind.as_u16() {
+            30023 => AddressKind::Article,
+            30311 => AddressKind::LiveEvent,
+            31922 | 31923 => AddressKind::Calendar,
+            10000..=10099 | 30000..=30007 | 30015 | 30030 | 30063 => AddressKind::List,
+            _ => AddressKind::Other,
+        }
+    }
+}
+
+/// `d` tag for the store and filters, `None` for plain replaceable kinds.
+pub fn address_identifier(coordinate: &Coordinate) -> Option<&str> {
+    coordinate
+        .kind
+        .is_parameterized_replaceable()
+        .then_some(coordinate.identifier.as_str())
+}
+
+/// The exact event an `naddr` names.
+pub fn coordinate_filter(coordinate: &Coordinate) -> Filter {
+    let filter = Filter::new()
+        .kind(coordinate.kind)
+        .author(coordinate.public_key);
+    match address_identifier(coordinate) {
+        Some(identifier) => filter.identifier(identifier),
+        None => filter,
+    }
+}
+
+/// `true` when `event` is a version of the `coordinate`, relays may send more.
+pub fn matches_coordinate(event: &Event, coordinate: &Coordinate) -> bool {
+    event.kind == coordinate.kind
+        && event.pubkey == coordinate.public_key
+        && address_identifier(coordinate)
+            .is_none_or(|identifier| tag_value(event, "d").unwrap_or_default() == identifier)
+}
+
+/// First non-empty value of the first `name` tag.
+pub fn tag_value(event: &Event, name: &str) -> Option<String> {
+    event.iter_tags().find_map(|tag| match tag.as_vec() {
+        [kind, value, ..] if kind == name && !value.trim().is_empty() => {
+            Some(value.trim().to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Display title: `title`, then the older `name`, then the `d` tag.
+pub fn address_title(event: &Event) -> String {
+    tag_value(event, "title")
+        .or_else(|| tag_value(event, "name"))
+        .or_else(|| tag_value(event, "d"))
+        .unwrap_or_default()
+}
+
+/// One public entry of a NIP-51 list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListItem {
+    Profile(PublicKey),
+    Note(EventId),
+    Address(Coordinate),
+    Hashtag(String),
+    /// http(s) only, other schemes are dropped
+    Link(String),
+    /// Shown as text, not a link
+    Relay(String),
+}
+
+pub fn list_items(event: &Event) -> Vec<ListItem> {
+    event
+        .iter_tags()
+        .filter_map(|tag| match tag.as_vec() {
+            [kind, value, ..] if kind == "p" => {
+                PublicKey::from_hex(value).ok().map(ListItem::Profile)
+            }
+            [kind, value, ..] if kind == "e" => EventId::from_hex(value).ok().map(ListItem::Note),
+            [kind, value, ..] if kind == "a" => {
+                Coordinate::parse(value).ok().map(ListItem::Address)
+            }
+            [kind, value, ..] if kind == "t" => Some(ListItem::Hashtag(value.to_lowercase())),
+            [kind, value, ..] if kind == "r" => website_url(value).map(ListItem::Link),
+            [kind, value, ..] if kind == "relay" => Some(ListItem::Relay(value.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// When a calendar event happens, as shown to the reader. Date-based events keep
+/// their `YYYY-MM-DD`, time-based ones are unix seconds.
+pub fn calendar_span(event: &Event) -> (Option<String>, Option<String>) {
+    let show = |value: String| match event.kind.as_u16() {
+        31923 => value
+            .parse::<u64>()
+            .map(|secs| Timestamp::from(secs).to_human_datetime())
+            .unwrap_or(value),
+        _ => value,
+    };
+    (
+        tag_value(event, "start").map(show),
+        tag_value(event, "end").map(show),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: &Keys, kind: u16, tags: Vec<Vec<&str>>) -> Event {
+        let tags = tags.iter().map(|t| Tag::parse(t).unwrap());
+        EventBuilder::new(Kind::from(kind), "", tags)
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn kinds_are_dispatched() {
+        assert_eq!(
+            AddressKind::of(Kind::LongFormTextNote),
+            AddressKind::Article
+        );
+        assert_eq!(AddressKind::of(Kind::LiveEvent), AddressKind::LiveEvent);
+        assert_eq!(AddressKind::of(Kind::from(31923)), AddressKind::Calendar);
+        assert_eq!(AddressKind::of(Kind::BookmarkSets), AddressKind::List);
+        assert_eq!(AddressKind::of(Kind::Bookmarks), AddressKind::List);
+        assert_eq!(AddressKind::of(Kind::from(31990)), AddressKind::Other);
+    }
+
+    #[test]
+    fn only_the_named_address_matches() {
+        let keys = Keys::generate();
+        let coordinate = Coordinate::new(Kind::LongFormTextNote, keys.public_key()).identifier("a");
+        assert!(matches_coordinate(
+            &event(&keys, 30023, vec![vec!["d", "a"]]),
+            &coordinate
+        ));
+        assert!(!matches_coordinate(
+            &event(&keys, 30023, vec![vec!["d", "b"]]),
+            &coordinate
+        ));
+        assert!(!matches_coordinate(
+            &event(&keys, 30024, vec![vec!["d", "a"]]),
+            &coordinate
+        ));
+        let stranger = Keys::generate();
+        assert!(!matches_coordinate(
+            &event(&stranger, 30023, vec![vec!["d", "a"]]),
+            &coordinate
+        ));
+
+        // replaceable kinds ignore the d tag
+        let bookmarks = Coordinate::new(Kind::Bookmarks, keys.public_key());
+        assert!(matches_coordinate(&event(&keys, 10003, vec![]), &bookmarks));
+    }
+
+    #[test]
+    fn list_items_and_titles() {
+        let keys = Keys::generate();
+        let friend = Keys::generate().public_key();
+        let list = event(
+            &keys,
+            30003,
+            vec![
+                vec!["d", "reading"],
+                vec!["name", "อ่านทีหลัง"],
+                vec!["p", &friend.to_hex()],
+                vec!["e", &EventId::all_zeros().to_hex()],
+                vec!["t", "Bitcoin"],
+                vec!["r", "https://example.com"],
+                vec!["relay", "wss://relay.siamstr.com"],
+                vec!["p", "not-a-key"],
+            ],
+        );
+        assert_eq!(address_title(&list), "อ่านทีหลัง");
+        assert_eq!(
+            list_items(&list),
+            vec![
+                ListItem::Profile(friend),
+                ListItem::Note(EventId::all_zeros()),
+                ListItem::Hashtag("bitcoin".to_string()),
+                ListItem::Link("https://example.com/".to_string()),
+                ListItem::Relay("wss://relay.siamstr.com".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_links_are_dropped() {
+        let list = event(
+            &Keys::generate(),
+            10003,
+            vec![
+                vec!["r", "javascript:alert(document.cookie)"],
+                vec!["r", "data:text/html,<script>alert(1)</script>"],
+                vec!["r", "http://example.com/a"],
+            ],
+        );
+        assert_eq!(
+            list_items(&list),
+            vec![ListItem::Link("http://example.com/a".to_string())]
+        );
+    }
+
+    #[test]
+    fn calendar_dates_and_times() {
+        let keys = Keys::generate();
+        let day = event(&keys, 31922, vec![vec!["start", "2024-05-01"]]);
+        assert_eq!(calendar_span(&day), (Some("2024-05-01".to_string()), None));
+        let meetup = event(
+            &keys,
+            31923,
+            vec![vec!["start", "1714550400"], vec!["end", "1714557600"]],
+        );
+        assert_eq!(
+            calendar_span(&meetup),
+            (
+                Some("2024-05-01T08:00:00Z".to_string()),
+                Some("2024-05-01T10:00:00Z".to_string())
+            )
+        );
+    }
+}
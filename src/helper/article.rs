@@ -0,0 +1,204 @@
+use lazy_static::lazy_static;
+use nostr_sdk::prelude::*;
+use pulldown_cmark::{html, CowStr, Event as MdEvent, Options, Parser, Tag as MdTag, TagEnd};
+use regex::Regex;
+
+lazy_static! {
+    static ref NOSTR_URI_REGEX: Regex =
+        Regex::new(r"nostr:(n(?:pub|profile|event|ote|addr)1[acdefghjklmnpqrstuvwxyz023456789]+)")
+            .unwrap();
+}
+
+/// A NIP-23 kind-30023 with its metadata tags pulled out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Article {
+    pub identifier: String,
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    pub image: Option<String>,
+    /// First publication, `created_at` moves with every edit
+    pub published_at: Option<Timestamp>,
+    pub content: String,
+}
+
+impl Article {
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.kind != Kind::LongFormTextNote {
+            return None;
+        }
+        let mut article = Self {
+            identifier: String::new(),
+            title: None,
+            summary: None,
+            image: None,
+            published_at: None,
+            content: event.content().to_string(),
+        };
+        for tag in event.iter_tags() {
+            let [kind, value, ..] = tag.as_vec() else {
+                continue;
+            };
+            let value = value.trim();
+            match kind.as_str() {
+                "d" => article.identifier = value.to_string(),
+                _ if value.is_empty() => (),
+                "title" => article.title = Some(value.to_string()),
+                "summary" => article.summary = Some(value.to_string()),
+                "image" => article.image = Some(value.to_string()),
+                "published_at" => {
+                    article.published_at = value.parse::<u64>().ok().map(Timestamp::from)
+                }
+                _ => (),
+            }
+        }
+        Some(article)
+    }
+
+    /// `naddr` pointing at the newest version of this article.
+    pub fn naddr(&self, author: PublicKey) -> String {
+        Coordinate::new(Kind::LongFormTextNote, author)
+            .identifier(&self.identifier)
+            .to_bech32()
+            .unwrap_or_default()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Plain text with every `nostr:` reference turned into a link to `/nostr/...`.
+fn link_references(text: &str, label: &impl Fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in NOSTR_URI_REGEX.captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        out.push_str(&escape_html(&text[last..whole.start()]));
+        out.push_str(&format!(
+            r#"<a href="/nostr/{}">{}</a>"#,
+            &caps[1],
+            escape_html(&label(&caps[1]))
+        ));
+        last = whole.end();
+    }
+    out.push_str(&escape_html(&text[last..]));
+    out
+}
+
+/// Article Markdown as sanitized HTML. `nostr:` links and bare references point
+/// at `/nostr/...`, bare ones are shown as `label(entity)`.
+pub fn markdown_to_html(markdown: &str, label: impl Fn(&str) -> String) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    // text inside code and links is left as written
+    let mut verbatim = 0_usize;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        MdEvent::Start(MdTag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            verbatim += 1;
+            let dest_url = match dest_url.strip_prefix("nostr:") {
+                Some(entity) => CowStr::from(format!("/nostr/{}", entity)),
+                None => dest_url,
+            };
+            MdEvent::Start(MdTag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            })
+        }
+        MdEvent::Start(MdTag::CodeBlock(kind)) => {
+            verbatim += 1;
+            MdEvent::Start(MdTag::CodeBlock(kind))
+        }
+        MdEvent::End(end @ (TagEnd::Link | TagEnd::CodeBlock)) => {
+            verbatim = verbatim.saturating_sub(1);
+            MdEvent::End(end)
+        }
+        MdEvent::Text(text) if verbatim == 0 && NOSTR_URI_REGEX.is_match(&text) => {
+            MdEvent::InlineHtml(CowStr::from(link_references(&text, &label)))
+        }
+        other => other,
+    });
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events);
+    ammonia::Builder::default()
+        .link_rel(Some("noopener noreferrer"))
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_tags() {
+        let tags = [
+            vec!["d", "hello-world"],
+            vec!["title", "สวัสดีชาวโลก"],
+            vec!["summary", "first post"],
+            vec!["image", "https://example.com/cover.jpg"],
+            vec!["published_at", "1700000000"],
+            vec!["t", "siamstr"],
+        ]
+        .iter()
+        .map(|t| Tag::parse(t).unwrap())
+        .collect::<Vec<_>>();
+        let keys = Keys::generate();
+        let event = EventBuilder::new(Kind::LongFormTextNote, "# Hi", tags)
+            .to_event(&keys)
+            .unwrap();
+        let article = Article::from_event(&event).unwrap();
+        assert_eq!(article.identifier, "hello-world");
+        assert_eq!(article.title.as_deref(), Some("สวัสดีชาวโลก"));
+        assert_eq!(article.summary.as_deref(), Some("first post"));
+        assert_eq!(
+            article.image.as_deref(),
+            Some("https://example.com/cover.jpg")
+        );
+        assert_eq!(article.published_at, Some(Timestamp::from(1700000000)));
+        let coordinate = Coordinate::from_bech32(article.naddr(keys.public_key())).unwrap();
+        assert_eq!(coordinate.identifier, "hello-world");
+
+        let note = EventBuilder::text_note("hi", []).to_event(&keys).unwrap();
+        assert_eq!(Article::from_event(&note), None);
+    }
+
+    #[test]
+    fn markdown_keeps_structure_and_drops_scripts() {
+        let html = markdown_to_html(
+            "# Title\n\n- one\n- [link](https://example.com)\n\n<script>alert(1)</script>\n\n<a href=\"javascript:alert(1)\">x</a>",
+            |entity| entity.to_string(),
+        );
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<li>one</li>"));
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn nostr_references_link_to_the_app() {
+        let npub = Keys::generate().public_key().to_bech32().unwrap();
+        let markdown = format!("by nostr:{npub} and [me](nostr:{npub})\n\n```\nnostr:{npub}\n```");
+        let html = markdown_to_html(&markdown, |_| "@vaz".to_string());
+        assert!(html.contains(&format!(
+            r#"<a href="/nostr/{npub}" rel="noopener noreferrer">@vaz</a>"#
+        )));
+        assert!(html.contains(&format!(
+            r#"<a href="/nostr/{npub}" rel="noopener noreferrer">me</a>"#
+        )));
+        assert!(html.contains(&format!("<code>nostr:{npub}\n</code>")));
+    }
+}
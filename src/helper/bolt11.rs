@@ -0,0 +1,240 @@
+use nostr_sdk::hashes::{sha256, Hash};
+use nostr_sdk::nostr::bech32::primitives::decode::CheckedHrpstring;
+use nostr_sdk::nostr::bech32::{Bech32, Fe32, Fe32IterExt};
+use nostr_sdk::nostr::util::hex;
+use nostr_sdk::prelude::*;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+
+/// 7 words of timestamp
+const TIMESTAMP_WORDS: usize = 7;
+/// 65 byte recoverable signature
+const SIGNATURE_WORDS: usize = 104;
+/// BOLT11 default when there is no `x` field
+const DEFAULT_EXPIRY: u64 = 3600;
+
+/// Chain an invoice pays on, from its prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Longest prefixes first, `lnbc` is also the start of `lnbcrt`.
+    fn from_hrp(hrp: &str) -> Option<Self> {
+        [
+            ("lnbcrt", Network::Regtest),
+            ("lnbc", Network::Bitcoin),
+            ("lntbs", Network::Signet),
+            ("lntb", Network::Testnet),
+        ]
+        .into_iter()
+        .find_map(|(prefix, network)| hrp.starts_with(prefix).then_some(network))
+    }
+
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Network::Bitcoin => None,
+            Network::Testnet => Some("testnet"),
+            Network::Signet => Some("signet"),
+            Network::Regtest => Some("regtest"),
+        }
+    }
+}
+
+/// Amount encoded in a BOLT11 human readable part, in millisats.
+pub fn bolt11_amount_msat(invoice: &str) -> Option<u64> {
+    let invoice = invoice.to_lowercase();
+    let hrp = &invoice[..invoice.rfind('1')?];
+    // strip `ln` + network prefix, what is left is the amount and multiplier
+    let amount = hrp.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    if amount.is_empty() {
+        return None;
+    }
+    let (digits, multiplier) = match amount.chars().last()? {
+        c if c.is_ascii_digit() => (amount, None),
+        c => (&amount[..amount.len() - 1], Some(c)),
+    };
+    let value: u64 = digits.parse().ok()?;
+    // value in BTC times the multiplier, as msat
+    match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some('m') => value.checked_mul(100_000_000),
+        Some('u') => value.checked_mul(100_000),
+        Some('n') => value.checked_mul(100),
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        _ => None,
+    }
+}
+
+/// The parts of a BOLT11 payment request worth showing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    pub network: Network,
+    pub amount_msat: Option<u64>,
+    pub created_at: Timestamp,
+    /// Seconds after `created_at`
+    pub expiry: u64,
+    pub description: Option<String>,
+    /// Hex, set instead of `description` for long descriptions
+    pub description_hash: Option<String>,
+    /// Hex node id, from the `n` field or recovered from the signature
+    pub payee: Option<String>,
+    /// Hex
+    pub payment_hash: Option<String>,
+}
+
+impl Invoice {
+    pub fn decode(invoice: &str) -> Result<Self, String> {
+        let invoice = invoice.trim().to_lowercase();
+        let invoice = invoice.strip_prefix("lightning:").unwrap_or(&invoice);
+        let checked = CheckedHrpstring::new::<Bech32>(invoice).map_err(|e| e.to_string())?;
+        let hrp = checked.hrp().to_lowercase();
+        let network = Network::from_hrp(&hrp).ok_or("not a lightning invoice")?;
+        // the checksum is valid, so the data part is `hrp1<words><6 checksum words>`
+        let words: Vec<Fe32> = invoice[hrp.len() + 1..invoice.len() - 6]
+            .chars()
+            .map(Fe32::from_char)
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        if words.len() < TIMESTAMP_WORDS + SIGNATURE_WORDS {
+            return Err("invoice too short".to_string());
+        }
+        let (data, signature) = words.split_at(words.len() - SIGNATURE_WORDS);
+        let mut decoded = Self {
+            network,
+            amount_msat: bolt11_amount_msat(invoice),
+            created_at: Timestamp::from(to_int(&data[..TIMESTAMP_WORDS])),
+            expiry: DEFAULT_EXPIRY,
+            description: None,
+            description_hash: None,
+            payee: None,
+            payment_hash: None,
+        };
+        let mut fields = &data[TIMESTAMP_WORDS..];
+        while fields.len() >= 3 {
+            let len = to_int(&fields[1..3]) as usize;
+            let Some(value) = fields.get(3..3 + len) else {
+                return Err("truncated field".to_string());
+            };
+            match fields[0].to_u8() {
+                // p
+                1 if len == 52 => decoded.payment_hash = Some(hex::encode(to_bytes(value))),
+                // d
+                13 => decoded.description = String::from_utf8(to_bytes(value)).ok(),
+                // h
+                23 if len == 52 => decoded.description_hash = Some(hex::encode(to_bytes(value))),
+                // x
+                6 => decoded.expiry = to_int(value),
+                // n
+                19 if len == 53 => decoded.payee = Some(hex::encode(to_bytes(value))),
+                _ => (),
+            }
+            fields = &fields[3 + len..];
+        }
+        if decoded.payee.is_none() {
+            decoded.payee = recover_payee(&hrp, data, signature);
+        }
+        Ok(decoded)
+    }
+
+    pub fn expires_at(&self) -> Timestamp {
+        Timestamp::from(self.created_at.as_u64().saturating_add(self.expiry))
+    }
+
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at()
+    }
+}
+
+/// Big-endian number from 5-bit words.
+fn to_int(words: &[Fe32]) -> u64 {
+    words
+        .iter()
+        .fold(0_u64, |acc, word| (acc << 5) | u64::from(word.to_u8()))
+}
+
+/// Field values drop the leftover bits.
+fn to_bytes(words: &[Fe32]) -> Vec<u8> {
+    words.iter().copied().fes_to_bytes().collect()
+}
+
+/// Node id that signed `hrp` and `data`, also proving the signature valid.
+fn recover_payee(hrp: &str, data: &[Fe32], signature: &[Fe32]) -> Option<String> {
+    let signature = to_bytes(signature);
+    let mut preimage = hrp.as_bytes().to_vec();
+    // the signed data pads the leftover bits with zeros to a whole byte
+    preimage.extend(
+        data.iter()
+            .copied()
+            .chain([Fe32::Q; 2])
+            .fes_to_bytes()
+            .take((data.len() * 5).div_ceil(8)),
+    );
+    let digest = sha256::Hash::hash(&preimage);
+    let id = RecoveryId::from_i32(i32::from(*signature.get(64)?)).ok()?;
+    let signature = RecoverableSignature::from_compact(&signature[..64], id).ok()?;
+    let message = Message::from_digest_slice(digest.as_byte_array()).ok()?;
+    let payee = Secp256k1::verification_only()
+        .recover_ecdsa(&message, &signature)
+        .ok()?;
+    Some(hex::encode(payee.serialize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BOLT11 spec: "Please send $3 for a cup of coffee to the same peer,
+    // within one minute"
+    const COFFEE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+
+    #[test]
+    fn decodes_the_spec_example() {
+        let invoice = Invoice::decode(COFFEE).unwrap();
+        assert_eq!(invoice.network, Network::Bitcoin);
+        assert_eq!(invoice.amount_msat, Some(250_000_000));
+        assert_eq!(invoice.created_at, Timestamp::from(1496314658));
+        assert_eq!(invoice.expiry, 60);
+        assert_eq!(invoice.description.as_deref(), Some("1 cup coffee"));
+        assert_eq!(
+            invoice.payment_hash.as_deref(),
+            Some("0001020304050607080900010203040506070809000102030405060708090102")
+        );
+        assert_eq!(
+            invoice.payee.as_deref(),
+            Some("03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad")
+        );
+        assert!(invoice.is_expired(Timestamp::from(1496314658 + 60)));
+        assert!(!invoice.is_expired(Timestamp::from(1496314658 + 59)));
+    }
+
+    #[test]
+    fn rejects_broken_invoices() {
+        let mut tampered = COFFEE.to_string();
+        tampered.replace_range(20..21, "q");
+        assert!(Invoice::decode(&tampered).is_err());
+        assert!(Invoice::decode("lnbc1").is_err());
+        assert!(Invoice::decode("npub1xyz").is_err());
+    }
+
+    #[test]
+    fn bolt11_amounts() {
+        assert_eq!(bolt11_amount_msat("lnbc2500u1pvjluez"), Some(250_000_000));
+        assert_eq!(bolt11_amount_msat("lnbc21n1pjexample"), Some(2_100));
+        assert_eq!(bolt11_amount_msat("lnbc10m1pjexample"), Some(1_000_000_000));
+        assert_eq!(bolt11_amount_msat("lnbc1pvjluez"), None);
+    }
+
+    #[test]
+    fn network_from_prefix() {
+        assert_eq!(Network::from_hrp("lnbcrt10u"), Some(Network::Regtest));
+        assert_eq!(Network::from_hrp("lnbc10u"), Some(Network::Bitcoin));
+        assert_eq!(Network::from_hrp("lntbs1m"), Some(Network::Signet));
+        assert_eq!(Network::from_hrp("lntb"), Some(Network::Testnet));
+        assert_eq!(Network::from_hrp("lnurl"), None);
+    }
+}
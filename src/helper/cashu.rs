@@ -0,0 +1,371 @@
+use nostr_sdk::hashes::{sha256, Hash};
+use nostr_sdk::nostr::base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use nostr_sdk::nostr::base64::Engine;
+use nostr_sdk::nostr::util::hex;
+use serde::{Deserialize, Serialize};
+
+/// NUT-00 domain separator for `hash_to_curve`.
+const DOMAIN_SEPARATOR: &[u8] = b"Secp256k1_HashToCurve_Cashu_";
+
+/// Proofs from one mint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintProofs {
+    pub mint: String,
+    pub proofs: Vec<Proof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    pub amount: u64,
+    pub secret: String,
+}
+
+/// A `cashuA` (V3, JSON) or `cashuB` (V4, CBOR) token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CashuToken {
+    pub mints: Vec<MintProofs>,
+    pub unit: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenV3 {
+    token: Vec<TokenV3Entry>,
+    unit: Option<String>,
+    memo: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenV3Entry {
+    mint: String,
+    proofs: Vec<ProofV3>,
+}
+
+#[derive(Deserialize)]
+struct ProofV3 {
+    amount: u64,
+    secret: String,
+}
+
+/// V4 keeps the short keys of the spec, keyset ids and signatures are skipped.
+#[derive(Deserialize)]
+struct TokenV4 {
+    m: String,
+    u: String,
+    d: Option<String>,
+    t: Vec<TokenV4Entry>,
+}
+
+#[derive(Deserialize)]
+struct TokenV4Entry {
+    p: Vec<ProofV4>,
+}
+
+#[derive(Deserialize)]
+struct ProofV4 {
+    a: u64,
+    s: String,
+}
+
+impl CashuToken {
+    pub fn decode(token: &str) -> Result<Self, String> {
+        let token = token.trim();
+        let token = token.strip_prefix("cashu:").unwrap_or(token);
+        let (version, payload) = token.split_at_checked(6).ok_or("not a cashu token")?;
+        // tokens in the wild mix the standard and url safe alphabets
+        let payload: String = payload
+            .trim_end_matches('=')
+            .chars()
+            .map(|c| match c {
+                '+' => '-',
+                '/' => '_',
+                c => c,
+            })
+            .collect();
+        let bytes = URL_SAFE_NO_PAD.decode(payload).map_err(|e| e.to_string())?;
+        match version {
+            "cashuA" => {
+                let v3: TokenV3 = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+                Ok(Self {
+                    mints: v3
+                        .token
+                        .into_iter()
+                        .map(|entry| MintProofs {
+                            mint: entry.mint,
+                            proofs: entry
+                                .proofs
+                                .into_iter()
+                                .map(|p| Proof {
+                                    amount: p.amount,
+                                    secret: p.secret,
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                    unit: v3.unit.unwrap_or_else(|| "sat".to_string()),
+                    memo: v3.memo,
+                })
+            }
+            "cashuB" => {
+                let v4: TokenV4 =
+                    ciborium::from_reader(bytes.as_slice()).map_err(|e| e.to_string())?;
+                Ok(Self {
+                    mints: vec![MintProofs {
+                        mint: v4.m,
+                        proofs: v4
+                            .t
+                            .into_iter()
+                            .flat_map(|entry| entry.p)
+                            .map(|p| Proof {
+                                amount: p.a,
+                                secret: p.s,
+                            })
+                            .collect(),
+                    }],
+                    unit: v4.u,
+                    memo: v4.d,
+                })
+            }
+            _ => Err("unknown token version".to_string()),
+        }
+    }
+
+    /// Total of every proof, `None` when a crafted token overflows `u64`.
+    pub fn amount(&self) -> Option<u64> {
+        self.proofs()
+            .try_fold(0_u64, |total, p| total.checked_add(p.amount))
+    }
+
+    pub fn proof_count(&self) -> usize {
+        self.proofs().count()
+    }
+
+    fn proofs(&self) -> impl Iterator<Item = &Proof> {
+        self.mints.iter().flat_map(|mint| mint.proofs.iter())
+    }
+}
+
+/// NUT-00 `hash_to_curve`, the `Y` a mint indexes a secret by.
+pub fn hash_to_curve(message: &[u8]) -> secp256k1::PublicKey {
+    let msg_hash = sha256::Hash::hash(&[DOMAIN_SEPARATOR, message].concat());
+    let mut counter: u32 = 0;
+    loop {
+        let mut preimage = msg_hash.as_byte_array().to_vec();
+        preimage.extend(counter.to_le_bytes());
+        let hash = sha256::Hash::hash(&preimage);
+        let mut candidate = vec![0x02];
+        candidate.extend(hash.as_byte_array());
+        if let Ok(point) = secp256k1::PublicKey::from_slice(&candidate) {
+            return point;
+        }
+        counter += 1;
+    }
+}
+
+/// NUT-07 answer for a set of proofs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStates {
+    pub unspent: usize,
+    pub pending: usize,
+    pub spent: usize,
+}
+
+#[derive(Serialize)]
+struct CheckStateRequest {
+    #[serde(rename = "Ys")]
+    ys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CheckStateResponse {
+    states: Vec<ProofState>,
+}
+
+#[derive(Deserialize)]
+struct ProofState {
+    state: String,
+}
+
+/// Ask `mint` which of `proofs` were spent.
+pub async fn check_states(mint: &str, proofs: &[Proof]) -> Result<ProofStates, String> {
+    let ys = proofs
+        .iter()
+        .map(|p| hex::encode(hash_to_curve(p.secret.as_bytes()).serialize()))
+        .collect();
+    let url = format!("{}/v1/checkstate", mint.trim_end_matches('/'));
+    let response: CheckStateResponse = reqwest::Client::new()
+        .post(url)
+        .json(&CheckStateRequest { ys })
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut states = ProofStates::default();
+    for proof in response.states {
+        match proof.state.as_str() {
+            "UNSPENT" => states.unspent += 1,
+            "PENDING" => states.pending += 1,
+            "SPENT" => states.spent += 1,
+            _ => (),
+        }
+    }
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    // NUT-00 example token
+    const V3: &str = "cashuAeyJ0b2tlbiI6W3sibWludCI6Imh0dHBzOi8vODMzMy5zcGFjZTozMzM4IiwicHJvb2ZzIjpbeyJhbW91bnQiOjIsImlkIjoiMDA5YTFmMjkzMjUzZTQxZSIsInNlY3JldCI6IjQwNzkxNWJjMjEyYmU2MWE3N2UzZTZkMmFlYjRjNzI3OTgwYmRhNTFjZDA2YTZhZmMyOWUyODYxNzY4YTc4MzciLCJDIjoiMDJiYzkwOTc5OTdkODFhZmIyY2M3MzQ2YjVlNDM0NWE5MzQ2YmQyYTUwNmViNzk1ODU5OGE3MmYwY2Y4NTE2M2VhIn0seyJhbW91bnQiOjgsImlkIjoiMDA5YTFmMjkzMjUzZTQxZSIsInNlY3JldCI6ImZlMTUxMDkzMTRlNjFkNzc1NmIwZjhlZTBmMjNhNjI0YWNhYTNmNGUwNDJmNjE0MzNjNzI4YzcwNTdiOTMxYmUiLCJDIjoiMDI5ZThlNTA1MGI4OTBhN2Q2YzA5NjhkYjE2YmMxZDVkNWZhMDQwZWExZGUyODRmNmVjNjlkNjEyOTlmNjcxMDU5In1dfV0sInVuaXQiOiJzYXQiLCJtZW1vIjoiVGhhbmsgeW91LiJ9";
+
+    fn v4(mint: &str) -> String {
+        let token = ciborium::Value::Map(vec![
+            ("m".into(), mint.into()),
+            ("u".into(), "sat".into()),
+            (
+                "t".into(),
+                ciborium::Value::Array(vec![ciborium::Value::Map(vec![
+                    ("i".into(), ciborium::Value::Bytes(vec![0, 0xad])),
+                    (
+                        "p".into(),
+                        ciborium::Value::Array(
+                            [(1, "first"), (4, "second")]
+                                .into_iter()
+                                .map(|(amount, secret)| {
+                                    ciborium::Value::Map(vec![
+                                        ("a".into(), amount.into()),
+                                        ("s".into(), secret.into()),
+                                        ("c".into(), ciborium::Value::Bytes(vec![2; 33])),
+                                    ])
+                                })
+                                .collect(),
+                        ),
+                    ),
+                ])]),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&token, &mut bytes).unwrap();
+        format!("cashuB{}", URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    #[test]
+    fn decodes_v3_json() {
+        let token = CashuToken::decode(V3).unwrap();
+        assert_eq!(token.mints[0].mint, "https://8333.space:3338");
+        assert_eq!(token.unit, "sat");
+        assert_eq!(token.memo.as_deref(), Some("Thank you."));
+        assert_eq!(token.amount(), Some(10));
+        assert_eq!(token.proof_count(), 2);
+    }
+
+    #[test]
+    fn decodes_v4_cbor() {
+        let token = CashuToken::decode(&v4("http://localhost:3338")).unwrap();
+        assert_eq!(token.mints[0].mint, "http://localhost:3338");
+        assert_eq!(token.amount(), Some(5));
+        assert_eq!(token.proof_count(), 2);
+        assert_eq!(token.memo, None);
+        assert!(CashuToken::decode("cashuBnotcbor").is_err());
+    }
+
+    #[test]
+    fn overflowing_amounts_are_refused() {
+        let json = format!(
+            r#"{{"token":[{{"mint":"https://mint.example","proofs":[{{"amount":{max},"secret":"a"}},{{"amount":1,"secret":"b"}}]}}]}}"#,
+            max = u64::MAX
+        );
+        let token = CashuToken::decode(&format!("cashuA{}", URL_SAFE_NO_PAD.encode(json))).unwrap();
+        assert_eq!(token.proof_count(), 2);
+        assert_eq!(token.amount(), None);
+    }
+
+    #[test]
+    fn hash_to_curve_vectors() {
+        let point =
+            |message: &str| hex::encode(hash_to_curve(&hex::decode(message).unwrap()).serialize());
+        assert_eq!(
+            point("0000000000000000000000000000000000000000000000000000000000000000"),
+            "024cce997d3b518f739663b757deaec95bcd9473c30a14ac2fd04023a739d1a725"
+        );
+        assert_eq!(
+            point("0000000000000000000000000000000000000000000000000000000000000001"),
+            "022e7158e11c9506f1aa4248bf531298daa7febd6194f003edcd9b93ade6253acf"
+        );
+    }
+
+    /// Mint answering one NUT-07 request, the first `Y` is spent.
+    fn stand_in_mint() -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let states: Vec<String> = request["Ys"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, y)| {
+                    let state = if i == 0 { "SPENT" } else { "UNSPENT" };
+                    format!(r#"{{"Y":{},"state":"{}","witness":null}}"#, y, state)
+                })
+                .collect();
+            let body = format!(r#"{{"states":[{}]}}"#, states.join(","));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            format!("{} {}", request_line.trim(), request)
+        });
+        (base, handle)
+    }
+
+    #[tokio::test]
+    async fn spent_check_against_stand_in_mint() {
+        let (base, handle) = stand_in_mint();
+        let token = CashuToken::decode(&v4(&format!("{}/", base))).unwrap();
+        let states = check_states(&token.mints[0].mint, &token.mints[0].proofs)
+            .await
+            .unwrap();
+        assert_eq!(
+            states,
+            ProofStates {
+                unspent: 1,
+                pending: 0,
+                spent: 1
+            }
+        );
+        let request = handle.join().unwrap();
+        assert!(request.starts_with("POST /v1/checkstate "));
+        let first = hex::encode(hash_to_curve(b"first").serialize());
+        assert!(request.contains(&first));
+    }
+}
@@ -1 +1,21 @@
+pub mod address;
+pub mod article;
+pub mod bolt11;
+pub mod cashu;
+pub mod mutes;
 pub mod note_regex;
+pub mod notifications;
+pub mod outbox;
+pub mod pagination;
+pub mod profiles;
+pub mod publish;
+pub mod reactions;
+pub mod relay_status;
+pub mod relays;
+pub mod search;
+pub mod share;
+pub mod signer;
+pub mod store;
+pub mod thread;
+pub mod validate;
+pub mod zap;
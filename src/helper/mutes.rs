@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::publish::sign_and_publish_to;
+use crate::helper::relays::{write_urls, RelayConfig};
+use crate::helper::thread::thread_refs;
+use crate::helper::validate::Validator;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One entry of a NIP-51 mute list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MuteItem {
+    Author(PublicKey),
+    Hashtag(String),
+    Word(String),
+    Thread(EventId),
+}
+
+/// Mute items from one side (public tags or encrypted content) of a kind-10000.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MuteItems {
+    pub authors: HashSet<PublicKey>,
+    pub hashtags: HashSet<String>,
+    pub words: Vec<String>,
+    pub threads: HashSet<EventId>,
+    /// Tags we do not understand, kept so republishing does not drop them
+    pub other: Vec<Tag>,
+}
+
+impl MuteItems {
+    pub fn from_tags<'a>(tags: impl IntoIterator<Item = &'a Tag>) -> Self {
+        let mut items = Self::default();
+        for tag in tags {
+            let parsed = match tag.as_vec() {
+                [kind, value, ..] if kind == "p" => {
+                    PublicKey::from_hex(value).ok().map(MuteItem::Author)
+                }
+                [kind, value, ..] if kind == "t" => Some(MuteItem::Hashtag(value.clone())),
+                [kind, value, ..] if kind == "word" => Some(MuteItem::Word(value.clone())),
+                [kind, value, ..] if kind == "e" => {
+                    EventId::from_hex(value).ok().map(MuteItem::Thread)
+                }
+                _ => None,
+            };
+            match parsed {
+                Some(item) => items.add(item),
+                None => items.other.push(tag.clone()),
+            }
+        }
+        items
+    }
+
+    /// Decrypted private content: a JSON array of tags.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: Vec<Vec<String>> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let tags: Vec<Tag> = raw.iter().filter_map(|t| Tag::parse(t).ok()).collect();
+        Ok(Self::from_tags(&tags))
+    }
+
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = self.authors.iter().map(|pk| Tag::public_key(*pk)).collect();
+        tags.extend(self.hashtags.iter().map(Tag::hashtag));
+        tags.extend(
+            self.words
+                .iter()
+                .filter_map(|word| Tag::parse(&["word", word.as_str()]).ok()),
+        );
+        tags.extend(self.threads.iter().map(|id| Tag::event(*id)));
+        tags.extend(self.other.iter().cloned());
+        tags
+    }
+
+    pub fn add(&mut self, item: MuteItem) {
+        match item {
+            MuteItem::Author(pk) => {
+                self.authors.insert(pk);
+            }
+            MuteItem::Hashtag(tag) => {
+                self.hashtags.insert(tag.to_lowercase());
+            }
+            MuteItem::Word(word) => {
+                let word = word.to_lowercase();
+                if !self.words.contains(&word) {
+                    self.words.push(word);
+                }
+            }
+            MuteItem::Thread(id) => {
+                self.threads.insert(id);
+            }
+        }
+    }
+
+    /// `true` when `event` is by a muted author, in a muted thread, or carries a
+    /// muted hashtag or word.
+    pub fn hides(&self, event: &Event) -> bool {
+        if self.authors.contains(&event.pubkey) || self.threads.contains(&event.id) {
+            return true;
+        }
+        let refs = thread_refs(event);
+        if refs.root.is_some_and(|root| self.threads.contains(&root)) {
+            return true;
+        }
+        let tagged = event.iter_tags().any(|tag| match tag.as_vec() {
+            [kind, value, ..] if kind == "t" => self.hashtags.contains(&value.to_lowercase()),
+            _ => false,
+        });
+        if tagged {
+            return true;
+        }
+        let content = event.content().to_lowercase();
+        self.words
+            .iter()
+            .any(|word| content.contains(word.as_str()))
+    }
+}
+
+/// The signed-in user's kind-10000 as loaded from relays.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MuteList {
+    pub public: MuteItems,
+    pub private: MuteItems,
+    /// Encrypted content as published, republished untouched
+    pub content: String,
+}
+
+impl MuteList {
+    pub fn hides(&self, event: &Event) -> bool {
+        self.public.hides(event) || self.private.hides(event)
+    }
+
+    pub fn to_builder(&self) -> EventBuilder {
+        EventBuilder::new(Kind::MuteList, self.content.clone(), self.public.to_tags())
+    }
+}
+
+/// Private items are NIP-44, older clients wrote NIP-04 (`?iv=` suffix).
+async fn decrypt_private(
+    client: &Client,
+    owner: PublicKey,
+    content: &str,
+) -> Result<MuteItems, String> {
+    let signer = client.signer().await.map_err(|e| e.to_string())?;
+    let json = if content.contains("?iv=") {
+        signer.nip04_decrypt(owner, content).await
+    } else {
+        signer.nip44_decrypt(owner, content).await
+    }
+    .map_err(|e| e.to_string())?;
+    MuteItems::from_json(&json)
+}
+
+/// Mute list of the extension user, shared through context. Guests share one key,
+/// so they get no list.
+#[derive(Clone, Copy)]
+pub struct Mutes {
+    /// `None` until a relay returned the list of `owner` or every relay asked
+    /// sent EOSE, an empty list only means it has none
+    list: RwSignal<Option<MuteList>>,
+    owner: StoredValue<Option<PublicKey>>,
+    loading: StoredValue<bool>,
+    client: RwSignal<Client>,
+    validator: Validator,
+    relays: RwSignal<Vec<RelayConfig>>,
+}
+
+impl Mutes {
+    pub fn new(
+        client: RwSignal<Client>,
+        validator: Validator,
+        relays: RwSignal<Vec<RelayConfig>>,
+    ) -> Self {
+        Self {
+            list: create_rw_signal(None),
+            owner: store_value(None),
+            loading: store_value(false),
+            client,
+            validator,
+            relays,
+        }
+    }
+
+    /// Tracked, `false` until a list is loaded.
+    pub fn hides(&self, event: &Event) -> bool {
+        self.list
+            .with(|list| list.as_ref().is_some_and(|list| list.hides(event)))
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.list.with(Option::is_some)
+    }
+
+    pub fn clear(&self) {
+        self.owner.set_value(None);
+        self.list.set(None);
+    }
+
+    /// Fetch the list of `owner`. Until a relay returns it, or every relay asked
+    /// sent EOSE, it stays unknown, see [`Mutes::retry`].
+    pub fn load(&self, owner: PublicKey) {
+        let previous = self
+            .owner
+            .try_update_value(|current| current.replace(owner))
+            .flatten();
+        if previous != Some(owner) {
+            self.list.set(None);
+        }
+        let busy = self
+            .loading
+            .try_update_value(|loading| std::mem::replace(loading, true))
+            .unwrap_or(true);
+        if busy {
+            return;
+        }
+        let mutes = *self;
+        let client = self.client.get_untracked();
+        spawn_local(async move {
+            let list = mutes.fetch(&client, owner).await;
+            mutes.loading.set_value(false);
+            if mutes.owner.get_value() != Some(owner) {
+                // signed in as someone else meanwhile
+                mutes.retry();
+                return;
+            }
+            match list {
+                Ok(list) => mutes.list.set(Some(list)),
+                Err(e) => log!("mute list fetch failed: {}", e),
+            }
+        });
+    }
+
+    /// Load again when the last try left the list unknown, called as relays connect.
+    pub fn retry(&self) {
+        let Some(owner) = self.owner.try_get_value().flatten() else {
+            return;
+        };
+        if self.list.with_untracked(Option::is_none) {
+            self.load(owner);
+        }
+    }
+
+    async fn fetch(&self, client: &Client, owner: PublicKey) -> Result<MuteList, String> {
+        let filter = Filter::new().author(owner).kind(Kind::MuteList);
+        let fetched = self
+            .validator
+            .fetch_all(client, None, vec![filter], FETCH_TIMEOUT)
+            .await?;
+        let newest = fetched
+            .events
+            .into_iter()
+            .filter(|event| event.pubkey == owner)
+            .max_by_key(|event| event.created_at);
+        let Some(event) = newest else {
+            // a relay that timed out may still hold the list
+            if !fetched.complete {
+                return Err("not every relay answered".to_string());
+            }
+            return Ok(MuteList::default());
+        };
+        let private = if event.content().is_empty() {
+            MuteItems::default()
+        } else {
+            decrypt_private(client, owner, event.content())
+                .await
+                .unwrap_or_else(|e| {
+                    log!("private mutes unreadable: {}", e);
+                    MuteItems::default()
+                })
+        };
+        Ok(MuteList {
+            public: MuteItems::from_tags(event.iter_tags()),
+            private,
+            content: event.content().to_string(),
+        })
+    }
+
+    /// Add `item` to the public list and publish the new kind-10000 to the
+    /// user's write relays. Refused while the list is unknown, publishing then
+    /// would replace the one on relays.
+    pub fn mute(&self, item: MuteItem) {
+        let Some(mut list) = self.list.get_untracked() else {
+            log!("mute list not loaded yet");
+            return;
+        };
+        list.public.add(item);
+        let builder = list.to_builder();
+        self.list.set(Some(list));
+        let client = self.client.get_untracked();
+        let urls = self.relays.with_untracked(|relays| write_urls(relays));
+        spawn_local(async move {
+            if let Err(e) = sign_and_publish_to(&client, urls, builder).await {
+                log!("mute list publish failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(keys: &Keys, content: &str, tags: Vec<Vec<&str>>) -> Event {
+        let tags = tags.iter().map(|t| Tag::parse(t).unwrap());
+        EventBuilder::text_note(content, tags)
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn tags_round_trip() {
+        let pk = Keys::generate().public_key();
+        let root = EventId::all_zeros();
+        let tags: Vec<Tag> = [
+            vec!["p".to_string(), pk.to_hex()],
+            vec!["t".to_string(), "Spam".to_string()],
+            vec!["word".to_string(), "airdrop".to_string()],
+            vec!["e".to_string(), root.to_hex()],
+            vec!["client".to_string(), "other".to_string()],
+        ]
+        .iter()
+        .map(|t| Tag::parse(t).unwrap())
+        .collect();
+        let items = MuteItems::from_tags(&tags);
+        assert!(items.authors.contains(&pk));
+        assert!(items.hashtags.contains("spam"));
+        assert_eq!(items.words, vec!["airdrop".to_string()]);
+        assert!(items.threads.contains(&root));
+        assert_eq!(items.other.len(), 1);
+        assert_eq!(MuteItems::from_tags(&items.to_tags()), items);
+    }
+
+    #[test]
+    fn private_items_are_a_json_tag_array() {
+        let pk = Keys::generate().public_key();
+        let json = format!(r#"[["p","{}"],["word","Scam"]]"#, pk.to_hex());
+        let items = MuteItems::from_json(&json).unwrap();
+        assert!(items.authors.contains(&pk));
+        assert_eq!(items.words, vec!["scam".to_string()]);
+        assert!(MuteItems::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn hides_authors_hashtags_words_and_threads() {
+        let spammer = Keys::generate();
+        let friend = Keys::generate();
+        let root = note(&friend, "root", vec![]);
+        let mut items = MuteItems::default();
+        items.add(MuteItem::Author(spammer.public_key()));
+        items.add(MuteItem::Hashtag("NFT".to_string()));
+        items.add(MuteItem::Word("Airdrop".to_string()));
+        items.add(MuteItem::Thread(root.id));
+
+        assert!(items.hides(&note(&spammer, "hello", vec![])));
+        assert!(items.hides(&note(&friend, "gm", vec![vec!["t", "nft"]])));
+        assert!(items.hides(&note(&friend, "free AIRDROP here", vec![])));
+        assert!(items.hides(&root));
+        let reply = note(
+            &friend,
+            "reply",
+            vec![vec!["e", &root.id.to_hex(), "", "root"]],
+        );
+        assert!(items.hides(&reply));
+        assert!(!items.hides(&note(&friend, "สวัสดี", vec![vec!["t", "siamstr"]])));
+    }
+
+    #[test]
+    fn republish_keeps_encrypted_content() {
+        let list = MuteList {
+            content: "ciphertext?iv=abc".to_string(),
+            ..Default::default()
+        };
+        let event = list.to_builder().to_event(&Keys::generate()).unwrap();
+        assert_eq!(event.kind, Kind::MuteList);
+        assert_eq!(event.content(), "ciphertext?iv=abc");
+    }
+}
@@ -1,12 +1,11 @@
-use std::ops::Deref;
+use std::ops::Range;
 
 use lazy_static::lazy_static;
-use leptos::{html::Span, *};
 use regex::Regex;
 
 const YOUTUBE_URL_TEXT_REGEX: &str = r#"(?x)
     (?:https?://)?               # Optional scheme
-    (?:www\.)?                   # Optional www
+    (?:www\.|m\.)?               # Optional www / mobile
     (?:youtube\.com|youtu\.be)   # Domain
     (?:                          # Group for different URL formats
         /watch\?v=               # watch?v=VIDEO_ID
@@ -20,147 +19,421 @@ const YOUTUBE_URL_TEXT_REGEX: &str = r#"(?x)
 // REGEX FROM @snort/system
 lazy_static! {
     static ref HASHTAG_REGEX: Regex =
-        Regex::new(r#"(#[^\s!@#$%^&*()=+.\/,\[{\]};:'\"?><]+)"#).unwrap();
-    // static ref TAG_REF_REGEX: Regex = Regex::new(r"(#\[\d+\])").unwrap();
-    static ref FILE_EXTENSION_REGEX: Regex = Regex::new(r"\.([\w]{1,7})$").unwrap();
-    static ref INVOICE_REGEX: Regex = Regex::new(r"(lnbc\w+)").unwrap();
-    static ref CASHU_REGEX: Regex =
-        Regex::new(r"(cashuA[A-Za-z0-9_-]{0,10000}={0,3})").unwrap();
+        Regex::new(r#"#([^\s!@#$%^&*()=+.\/,\[{\]};:'\"?><]+)"#).unwrap();
+    static ref INVOICE_REGEX: Regex = Regex::new(r"(?i)ln(?:bcrt|bc|tbs|tb)[0-9a-z]+").unwrap();
+    static ref CASHU_REGEX: Regex = Regex::new(r"cashu[AB][A-Za-z0-9_-]{0,10000}={0,3}").unwrap();
     static ref MENTION_NOSTR_ENTITY_REGEX: Regex =
-        Regex::new(r"@n(pub|profile|event|ote|addr)1[acdefghjklmnpqrstuvwxyz023456789]+").unwrap();
-    static ref MARKDOWN_CODE_REGEX: Regex = Regex::new(r"(?ms)(```.+?```)").unwrap();
-    static ref YOUTUBE_URL_REGEX: Regex =
-        Regex::new(r"\bhttps?://(www\.)?(youtube\.com|youtu\.?be)/.+\b").unwrap();
-    static ref YOUTUBE_EXTRACT_URL_REGEX: Regex =
-        Regex::new(YOUTUBE_URL_TEXT_REGEX).unwrap();
+        Regex::new(r"@(n(?:pub|profile|event|ote|addr)1[acdefghjklmnpqrstuvwxyz023456789]+)")
+            .unwrap();
+    static ref MARKDOWN_CODE_REGEX: Regex = Regex::new(r"(?s)```(.+?)```").unwrap();
+    static ref YOUTUBE_EXTRACT_URL_REGEX: Regex = Regex::new(YOUTUBE_URL_TEXT_REGEX).unwrap();
     static ref IMAGE_URL_REGEX: Regex =
-        Regex::new(r#"(?i)\bhttps?://\S+\.(jpg|jpeg|png|gif|bmp|webp)\b"#).unwrap();
-    static ref VIDEO_URL_REGEX: Regex =
-        Regex::new(r#"\bhttps?://\S+\.(mp4|wav|mov)\b"#).unwrap();
-    static ref OTHER_URL_REGEX: Regex = Regex::new(r#"(?i)\bhttps?://[^\s]+\b"#).unwrap();
-    static ref NOSTR_REGEX: Regex =
-        Regex::new(r"(?x)(?:nostr:)?n(pub|profile|event|ote|addr)1[acdefghjklmnpqrstuvwxyz023456789]+").unwrap();
+        Regex::new(r"(?i)\.(jpg|jpeg|png|gif|bmp|webp)(\?\S*)?$").unwrap();
+    static ref VIDEO_URL_REGEX: Regex = Regex::new(r"(?i)\.(mp4|wav|mov|webm)(\?\S*)?$").unwrap();
+    static ref OTHER_URL_REGEX: Regex = Regex::new(r"(?i)\bhttps?://[^\s]+[\w/=]").unwrap();
+    static ref NOSTR_REGEX: Regex = Regex::new(
+        r"(?:nostr:)?(n(?:pub|profile|event|ote|addr)1[acdefghjklmnpqrstuvwxyz023456789]+)"
+    )
+    .unwrap();
+}
+
+/// What a slice of note content is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Text,
+    /// Tag without the leading `#`
+    Hashtag(String),
+    Url,
+    Image,
+    Video,
+    /// 11 character video id
+    YouTube(String),
+    Invoice,
+    Cashu,
+    /// Bech32 entity with any `nostr:`/`@` prefix removed
+    NostrRef(String),
+    /// Text between the fences, without the language line
+    CodeBlock(String),
+    LineBreak,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte range in the original content
+    pub span: Range<usize>,
+}
+
+impl Token {
+    pub fn text<'a>(&self, content: &'a str) -> &'a str {
+        &content[self.span.clone()]
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Pattern {
+    Url,
+    Nostr,
+    Mention,
+    Invoice,
+    Cashu,
+    Hashtag,
 }
 
-pub fn transform_text_to_html(text: &str) -> Vec<HtmlElement<Span>> {
-    let patterns: Vec<(&Regex, Box<dyn Fn(&regex::Captures) -> HtmlElement<_>>)> = vec![
-        (
-            HASHTAG_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="hashtag">{&caps[0].to_owned()}</span> },
-            ),
-        ),
-        (
-            IMAGE_URL_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="img"><img src={&caps[0].to_owned()}></img></span> },
-            ),
-        ),
-        (
-            INVOICE_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="invoice">{&caps[0].to_owned()}</span> },
-            ),
-        ),
-        (
-            CASHU_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="cashu">{&caps[0].to_owned()}</span> },
-            ),
-        ),
-        (
-            MENTION_NOSTR_ENTITY_REGEX.deref(),
-            Box::new(|caps: &regex::Captures| {
-                let url = format!("/nostr/{}", &caps[0].to_owned());
-                view! { <span class="nostr-entity"><iframe src={url} frameborder="0"></iframe></span> }
-            }),
-        ),
-        (
-            MARKDOWN_CODE_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="markdown"><code>{&caps[0].to_owned()}</code></span> },
-            ),
-        ),
-        (
-            YOUTUBE_URL_REGEX.deref(),
-            Box::new(|caps: &regex::Captures| {
-                let texts = &caps[0].to_owned();
-                let regex = YOUTUBE_EXTRACT_URL_REGEX.deref();
-                let url = regex
-                    .captures(&texts)
-                    .map(|mat| format!("https://youtube.com/embed/{}", &mat[1]));
-                view! { <span class="video"><iframe src={url} title="YouTube video player" frameborder="0" allowfullscreen></iframe></span> }
-            }),
-        ),
-        (
-            VIDEO_URL_REGEX.deref(),
-            Box::new(
-                |caps: &regex::Captures| view! { <span class="video"><video controls><source src={&caps[0].to_owned()}/></video></span> },
-            ),
-        ),
-        (
-            OTHER_URL_REGEX.deref(),
-            Box::new(|caps: &regex::Captures| {
-                let url = &caps[0].to_owned();
-                if IMAGE_URL_REGEX.is_match(url)
-                    || YOUTUBE_URL_REGEX.is_match(url)
-                    || VIDEO_URL_REGEX.is_match(url)
-                {
-                    return view! {<span/>};
-                };
-                view! { <span class="link"><a href={url} target="_blank">{url}</a></span> }
-            }),
-        ),
-        (
-            NOSTR_REGEX.deref(),
-            Box::new(|caps: &regex::Captures| {
-                let url = format!("/nostr/{}", &caps[0].to_owned());
-                view! { <span class="nostr-entity"><iframe src={url} frameborder="0"></iframe></span> }
-            }),
-        ),
-        // (
-        //     FILE_EXTENSION_REGEX.deref(),
-        //     Box::new(
-        //         |caps: &regex::Captures| view! { <span class="file-extension"><img src={&caps[0].to_owned()}></img></span> },
-        //     ),
-        // ),
-        // (
-        //     TAG_REF_REGEX.deref(),
-        //     Box::new(
-        //         |caps: &regex::Captures| view! { <span class="tag-ref">{&caps[0].to_owned()}</span> },
-        //     ),
-        // ),
+impl Pattern {
+    // earlier wins when two patterns start at the same byte
+    const ALL: [Pattern; 6] = [
+        Pattern::Url,
+        Pattern::Nostr,
+        Pattern::Mention,
+        Pattern::Invoice,
+        Pattern::Cashu,
+        Pattern::Hashtag,
     ];
-    let texts: Vec<Vec<&str>> = text.split("\n").map(|x| x.split(" ").collect()).collect();
-    let mut result = Vec::new();
-    let mut last_end: usize;
-
-    for text_n in texts.into_iter() {
-        for text in text_n.iter() {
-            last_end = 0;
-            for (regex, replacer) in &patterns {
-                for caps in regex.captures_iter(&text) {
-                    if let Some(mat) = caps.get(0) {
-                        if mat.start() > last_end {
-                            result.push(
-                                view! { <span>{&text[last_end..mat.start()].to_owned()}</span> },
-                            );
-                        }
-                        result.push(replacer(&caps));
-                        last_end = mat.end();
-                    }
-                }
-            }
-            if last_end < text.len() {
-                result.push(view! { <span>{&text[last_end..].to_owned()}</span> });
+
+    fn regex(&self) -> &'static Regex {
+        match self {
+            Pattern::Url => &OTHER_URL_REGEX,
+            Pattern::Nostr => &NOSTR_REGEX,
+            Pattern::Mention => &MENTION_NOSTR_ENTITY_REGEX,
+            Pattern::Invoice => &INVOICE_REGEX,
+            Pattern::Cashu => &CASHU_REGEX,
+            Pattern::Hashtag => &HASHTAG_REGEX,
+        }
+    }
+
+    /// First match at or after `from` that is not glued to an ASCII word before
+    /// it. Thai has no spaces between words, so Thai letters do not count.
+    fn find<'t>(&self, text: &'t str, from: usize) -> Option<regex::Captures<'t>> {
+        let mut at = from;
+        while let Some(caps) = self.regex().captures_at(text, at) {
+            let start = caps.get(0).map_or(text.len(), |m| m.start());
+            let glued = text[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !glued {
+                return Some(caps);
             }
-            result.push(view! { <span>" "</span> })
+            at = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        None
+    }
+
+    fn kind(&self, caps: &regex::Captures) -> TokenKind {
+        match self {
+            Pattern::Url => classify_url(&caps[0]),
+            Pattern::Nostr | Pattern::Mention => TokenKind::NostrRef(caps[1].to_string()),
+            Pattern::Invoice => TokenKind::Invoice,
+            Pattern::Cashu => TokenKind::Cashu,
+            Pattern::Hashtag => TokenKind::Hashtag(caps[1].to_string()),
+        }
+    }
+}
+
+fn classify_url(url: &str) -> TokenKind {
+    if let Some(caps) = YOUTUBE_EXTRACT_URL_REGEX.captures(url) {
+        TokenKind::YouTube(caps[1].to_string())
+    } else if IMAGE_URL_REGEX.is_match(url) {
+        TokenKind::Image
+    } else if VIDEO_URL_REGEX.is_match(url) {
+        TokenKind::Video
+    } else {
+        TokenKind::Url
+    }
+}
+
+/// Split note content into tokens covering every byte of `content` in order.
+pub fn tokenize(content: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut last_end = 0;
+    for caps in MARKDOWN_CODE_REGEX.captures_iter(content) {
+        let whole = caps.get(0).expect("code block");
+        tokenize_plain(content, last_end..whole.start(), &mut tokens);
+        tokens.push(Token {
+            kind: TokenKind::CodeBlock(code_body(&caps[1]).to_string()),
+            span: whole.range(),
+        });
+        last_end = whole.end();
+    }
+    tokenize_plain(content, last_end..content.len(), &mut tokens);
+    tokens
+}
+
+/// Text between the fences without the language line, as in ```` ```rust ````.
+fn code_body(inner: &str) -> &str {
+    match inner.split_once('\n') {
+        Some((info, code))
+            if info
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-#._".contains(c)) =>
+        {
+            code
+        }
+        _ => inner,
+    }
+}
+
+fn tokenize_plain(content: &str, range: Range<usize>, tokens: &mut Vec<Token>) {
+    let mut line_start = range.start;
+    for line in content[range.clone()].split('\n') {
+        let line_end = line_start + line.len();
+        tokenize_line(content, line_start..line_end, tokens);
+        if line_end < range.end {
+            tokens.push(Token {
+                kind: TokenKind::LineBreak,
+                span: line_end..line_end + 1,
+            });
+        }
+        line_start = line_end + 1;
+    }
+}
+
+fn tokenize_line(content: &str, range: Range<usize>, tokens: &mut Vec<Token>) {
+    let line = &content[range.clone()];
+    let offset = range.start;
+    let mut pos = 0;
+    while pos < line.len() {
+        let next = Pattern::ALL
+            .iter()
+            .filter_map(|p| p.find(line, pos).map(|caps| (p, caps)))
+            .min_by_key(|(_, caps)| caps.get(0).map(|m| m.start()));
+        let Some((pattern, caps)) = next else {
+            break;
+        };
+        let mat = caps.get(0).expect("match");
+        if mat.start() > pos {
+            tokens.push(Token {
+                kind: TokenKind::Text,
+                span: offset + pos..offset + mat.start(),
+            });
+        }
+        tokens.push(Token {
+            kind: pattern.kind(&caps),
+            span: offset + mat.start()..offset + mat.end(),
+        });
+        pos = mat.end();
+    }
+    if pos < line.len() {
+        tokens.push(Token {
+            kind: TokenKind::Text,
+            span: offset + pos..range.end,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(content)
+            .into_iter()
+            .map(|t| {
+                let text = t.text(content);
+                (t.kind, text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plain_thai_text_is_one_token() {
+        let content = "สวัสดีตอนเช้า ชาวสยามสเตอร์";
+        assert_eq!(kinds(content), vec![(TokenKind::Text, content)]);
+    }
+
+    #[test]
+    fn spans_cover_whole_content() {
+        let content = "gm #siamstr\nดูรูปนี้ https://image.nostr.build/abc.jpg แล้วไป nostr:npub1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sjpsxcu ```let x = 1;``` จบ";
+        let tokens = tokenize(content);
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(token.span.start, end);
+            end = token.span.end;
+        }
+        assert_eq!(end, content.len());
+    }
+
+    #[test]
+    fn hashtags_in_thai_note() {
+        let content = "ตลาดนัด #siamstr วันนี้ #กาแฟ!";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "ตลาดนัด "),
+                (TokenKind::Hashtag("siamstr".to_string()), "#siamstr"),
+                (TokenKind::Text, " วันนี้ "),
+                (TokenKind::Hashtag("กาแฟ".to_string()), "#กาแฟ"),
+                (TokenKind::Text, "!"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hash_inside_word_is_not_a_hashtag() {
+        let content = "issue#12";
+        assert_eq!(kinds(content), vec![(TokenKind::Text, content)]);
+    }
+
+    #[test]
+    fn hashtag_right_after_thai_text() {
+        let content = "สวัสดี#siamstr";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "สวัสดี"),
+                (TokenKind::Hashtag("siamstr".to_string()), "#siamstr"),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_breaks() {
+        let content = "บรรทัดแรก\n\nบรรทัดสาม";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "บรรทัดแรก"),
+                (TokenKind::LineBreak, "\n"),
+                (TokenKind::LineBreak, "\n"),
+                (TokenKind::Text, "บรรทัดสาม"),
+            ]
+        );
+    }
+
+    #[test]
+    fn media_urls_are_classified() {
+        let content = "https://nostr.build/i/cat.JPG https://v.nostr.build/clip.mp4 https://siamstr.com/about";
+        let tokens: Vec<TokenKind> = tokenize(content)
+            .into_iter()
+            .map(|t| t.kind)
+            .filter(|k| *k != TokenKind::Text)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![TokenKind::Image, TokenKind::Video, TokenKind::Url]
+        );
+    }
+
+    #[test]
+    fn url_drops_trailing_punctuation() {
+        let content = "(ดูที่ https://siamstr.com/feed).";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "(ดูที่ "),
+                (TokenKind::Url, "https://siamstr.com/feed"),
+                (TokenKind::Text, ")."),
+            ]
+        );
+    }
+
+    #[test]
+    fn url_fragment_is_not_a_hashtag() {
+        let content = "https://github.com/vazw/siamstr-feed#readme";
+        assert_eq!(kinds(content), vec![(TokenKind::Url, content)]);
+    }
+
+    #[test]
+    fn youtube_links() {
+        for url in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ?si=abc",
+            "https://youtube.com/shorts/dQw4w9WgXcQ",
+        ] {
+            let tokens = tokenize(url);
+            assert_eq!(
+                tokens[0].kind,
+                TokenKind::YouTube("dQw4w9WgXcQ".to_string())
+            );
         }
-        result.push(view! {
-            <span>
-                <br/>
-            </span>
-        })
     }
-    result
+
+    #[test]
+    fn nostr_references_strip_prefix() {
+        let npub = "npub1qqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sjpsxcu";
+        let content = format!("cc nostr:{} และ @{}", npub, npub);
+        let refs: Vec<TokenKind> = tokenize(&content)
+            .into_iter()
+            .map(|t| t.kind)
+            .filter(|k| matches!(k, TokenKind::NostrRef(_)))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                TokenKind::NostrRef(npub.to_string()),
+                TokenKind::NostrRef(npub.to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn invoice_and_cashu() {
+        let content = "จ่ายที่ lnbc10u1pjexample0 หรือ cashuAeyJ0b2tlbiI6W119";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "จ่ายที่ "),
+                (TokenKind::Invoice, "lnbc10u1pjexample0"),
+                (TokenKind::Text, " หรือ "),
+                (TokenKind::Cashu, "cashuAeyJ0b2tlbiI6W119"),
+            ]
+        );
+    }
+
+    #[test]
+    fn testnet_invoices() {
+        for invoice in [
+            "lntb20m1pexample",
+            "lntbs1u1pexample",
+            "lnbcrt500n1pexample",
+        ] {
+            assert_eq!(kinds(invoice), vec![(TokenKind::Invoice, invoice)]);
+        }
+    }
+
+    #[test]
+    fn cashu_v4_tokens() {
+        let content = "รับได้เลย cashuBo2FteBtodHRwczovL21pbnQuZXhhbXBsZS5jb20";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "รับได้เลย "),
+                (
+                    TokenKind::Cashu,
+                    "cashuBo2FteBtodHRwczovL21pbnQuZXhhbXBsZS5jb20"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_block_is_not_tokenized() {
+        let content = "โค้ด:\n```rust\nlet tag = \"#siamstr\";\n```\nจบ";
+        assert_eq!(
+            kinds(content),
+            vec![
+                (TokenKind::Text, "โค้ด:"),
+                (TokenKind::LineBreak, "\n"),
+                (
+                    TokenKind::CodeBlock("let tag = \"#siamstr\";\n".to_string()),
+                    "```rust\nlet tag = \"#siamstr\";\n```"
+                ),
+                (TokenKind::LineBreak, "\n"),
+                (TokenKind::Text, "จบ"),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_block_without_language() {
+        assert_eq!(
+            tokenize("```\nx = 1\n```")[0].kind,
+            TokenKind::CodeBlock("x = 1\n".to_string())
+        );
+        assert_eq!(
+            tokenize("```let x = 1;```")[0].kind,
+            TokenKind::CodeBlock("let x = 1;".to_string())
+        );
+    }
 }
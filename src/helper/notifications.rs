@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+
+use leptos::logging::log;
+use leptos::wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use leptos::*;
+use nostr_sdk::prelude::*;
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+
+use crate::helper::thread::thread_refs;
+use crate::helper::validate::Validator;
+use crate::helper::zap::{zap_receipt, Zappers};
+
+const STORAGE_KEY: &str = "siamstr-notifications";
+/// Characters of a note shown in the notification body.
+const BODY_CHARS: usize = 120;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoticeKind {
+    Reply,
+    Mention,
+    Reaction,
+    Zap,
+}
+
+/// What to tell the user about an event that tags them, and where a click goes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    pub title: String,
+    pub body: String,
+    pub path: String,
+}
+
+fn note_path(id: EventId) -> String {
+    format!("/nostr/{}", id.to_bech32().unwrap_or_default())
+}
+
+fn excerpt(content: &str) -> String {
+    let mut text: String = content.chars().take(BODY_CHARS).collect();
+    if content.chars().count() > BODY_CHARS {
+        text.push('…');
+    }
+    text
+}
+
+/// `None` for the user's own events and for ones that do not tag `me`. Zap
+/// receipts also need `zapper`, the key of `me`'s LNURL server.
+pub fn notice_for(event: &Event, me: &PublicKey, zapper: Option<PublicKey>) -> Option<Notice> {
+    if event.pubkey == *me || !event.public_keys().any(|pk| pk == me) {
+        return None;
+    }
+    match event.kind {
+        Kind::TextNote => {
+            let reply = thread_refs(event).parent().is_some();
+            Some(Notice {
+                kind: if reply {
+                    NoticeKind::Reply
+                } else {
+                    NoticeKind::Mention
+                },
+                title: if reply {
+                    "ตอบกลับคุณ"
+                } else {
+                    "กล่าวถึงคุณ"
+                }
+                .to_string(),
+                body: excerpt(event.content()),
+                path: note_path(event.id),
+            })
+        }
+        Kind::Reaction => {
+            let target = event.event_ids().last().copied()?;
+            let reaction = match event.content() {
+                "" | "+" => "👍",
+                content => content,
+            };
+            Some(Notice {
+                kind: NoticeKind::Reaction,
+                title: "รีแอคโน้ตของคุณ".to_string(),
+                body: excerpt(reaction),
+                path: note_path(target),
+            })
+        }
+        Kind::ZapReceipt => {
+            let receipt = zap_receipt(event)
+                .filter(|receipt| receipt.recipient == *me && receipt.signed_by(zapper))?;
+            Some(Notice {
+                kind: NoticeKind::Zap,
+                title: "ได้รับ Zap".to_string(),
+                body: format!("⚡ {} sats", receipt.sats),
+                path: receipt
+                    .note
+                    .map(note_path)
+                    .unwrap_or_else(|| format!("/nostr/{}", me.to_bech32().unwrap_or_default())),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Events tagging `me` from now on.
+pub fn notice_filter(me: PublicKey) -> Filter {
+    Filter::new()
+        .pubkey(me)
+        .kinds([Kind::TextNote, Kind::Reaction, Kind::ZapReceipt])
+        .since(Timestamp::now())
+}
+
+fn load_enabled() -> bool {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+        .is_some_and(|value| value == "on")
+}
+
+fn save_enabled(enabled: bool) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let _ = storage.set_item(STORAGE_KEY, if enabled { "on" } else { "off" });
+    }
+}
+
+fn permitted() -> bool {
+    Notification::permission() == NotificationPermission::Granted
+}
+
+/// Opt-in desktop notifications, shared through context. While a personal key
+/// is logged in, events tagging it raise a notification when the tab is hidden.
+#[derive(Clone, Copy)]
+pub struct Notifier {
+    enabled: RwSignal<bool>,
+    client: RwSignal<Client>,
+    validator: Validator,
+    zappers: Zappers,
+    sub_id: StoredValue<Option<SubscriptionId>>,
+    shown: StoredValue<HashSet<EventId>>,
+}
+
+impl Notifier {
+    pub fn new(client: RwSignal<Client>, validator: Validator, zappers: Zappers) -> Self {
+        Self {
+            enabled: create_rw_signal(load_enabled() && permitted()),
+            client,
+            validator,
+            zappers,
+            sub_id: store_value(None),
+            shown: store_value(HashSet::new()),
+        }
+    }
+
+    /// Tracked.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Ask the browser first, `on_denied` runs when it says no.
+    pub fn enable(&self, on_denied: impl FnOnce() + 'static) {
+        if permitted() {
+            self.enabled.set(true);
+            save_enabled(true);
+            return;
+        }
+        let enabled = self.enabled;
+        let callback = Closure::once_into_js(move |permission: JsValue| {
+            if permission.as_string().as_deref() == Some("granted") {
+                let _ = enabled.try_set(true);
+                save_enabled(true);
+            } else {
+                on_denied();
+            }
+        });
+        if let Err(e) =
+            Notification::request_permission_with_permission_callback(callback.unchecked_ref())
+        {
+            log!("notification permission: {:?}", e);
+        }
+    }
+
+    pub fn disable(&self) {
+        self.enabled.set(false);
+        save_enabled(false);
+    }
+
+    /// Subscribe for `me`, replacing any earlier subscription. `open` gets the
+    /// path of a clicked notification.
+    pub fn start(&self, me: PublicKey, open: impl Fn(&str) + Clone + 'static) {
+        self.stop();
+        let notifier = *self;
+        let client = self.client.get_untracked();
+        // known before subscribing, so a stop right away still finds it
+        let id = SubscriptionId::generate();
+        self.sub_id.set_value(Some(id.clone()));
+        // start looking up the wallet key before the first zap comes in
+        untrack(|| self.zappers.get(&me));
+        spawn_local(async move {
+            client
+                .subscribe_with_id(id.clone(), vec![notice_filter(me)], None)
+                .await;
+            let mut notifications = client.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                if notifier.sub_id.try_get_value().flatten().as_ref() != Some(&id) {
+                    // stopped or restarted
+                    break;
+                }
+                let RelayPoolNotification::Event {
+                    relay_url,
+                    subscription_id,
+                    event,
+                } = notification
+                else {
+                    continue;
+                };
+                if subscription_id != id || !notifier.validator.check(&relay_url, &event) {
+                    continue;
+                }
+                let first = notifier
+                    .shown
+                    .try_update_value(|shown| shown.insert(event.id))
+                    .unwrap_or(false);
+                if !first || !document().hidden() || !permitted() {
+                    continue;
+                }
+                let zapper = untrack(|| notifier.zappers.get(&me));
+                if let Some(notice) = notice_for(&event, &me, zapper) {
+                    show(&notice, event.id, open.clone());
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        if let Some(id) = self.sub_id.try_update_value(Option::take).flatten() {
+            let client = self.client.get_untracked();
+            spawn_local(async move { client.unsubscribe(id).await });
+        }
+    }
+}
+
+fn show(notice: &Notice, id: EventId, open: impl Fn(&str) + 'static) {
+    let mut options = NotificationOptions::new();
+    options.body(&notice.body).tag(&id.to_hex());
+    let notification = match Notification::new_with_options(&notice.title, &options) {
+        Ok(notification) => notification,
+        Err(e) => {
+            log!("notification failed: {:?}", e);
+            return;
+        }
+    };
+    let path = notice.path.clone();
+    let clicked = notification.clone();
+    let on_click = Closure::once_into_js(move || {
+        let _ = window().focus();
+        open(&path);
+        clicked.close();
+    });
+    notification.set_onclick(Some(on_click.unchecked_ref()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_mentions_reactions_and_zaps() {
+        let me = Keys::generate();
+        let them = Keys::generate();
+        let mine = EventBuilder::text_note("สวัสดี", []).to_event(&me).unwrap();
+
+        let reply = EventBuilder::text_note_reply("ดีครับ", &mine, None, None)
+            .to_event(&them)
+            .unwrap();
+        let notice = notice_for(&reply, &me.public_key(), None).unwrap();
+        assert_eq!(notice.kind, NoticeKind::Reply);
+        assert_eq!(notice.body, "ดีครับ");
+        assert_eq!(notice.path, note_path(reply.id));
+
+        let mention = EventBuilder::text_note("hi", [Tag::public_key(me.public_key())])
+            .to_event(&them)
+            .unwrap();
+        assert_eq!(
+            notice_for(&mention, &me.public_key(), None).unwrap().kind,
+            NoticeKind::Mention
+        );
+
+        let reaction = EventBuilder::reaction(&mine, "+").to_event(&them).unwrap();
+        let notice = notice_for(&reaction, &me.public_key(), None).unwrap();
+        assert_eq!(notice.kind, NoticeKind::Reaction);
+        assert_eq!(notice.body, "👍");
+        assert_eq!(notice.path, note_path(mine.id));
+
+        let request = EventBuilder::public_zap_request(
+            ZapRequestData::new(me.public_key(), []).event_id(mine.id),
+        )
+        .to_event(&them)
+        .unwrap();
+        let server = Keys::generate();
+        let receipt = EventBuilder::zap_receipt("lnbc210n1pjexample", None::<String>, &request)
+            .to_event(&server)
+            .unwrap();
+        // only the wallet's own server may claim a zap
+        assert_eq!(notice_for(&receipt, &me.public_key(), None), None);
+        let forged = EventBuilder::zap_receipt("lnbc210n1pjexample", None::<String>, &request)
+            .to_event(&them)
+            .unwrap();
+        assert_eq!(
+            notice_for(&forged, &me.public_key(), Some(server.public_key())),
+            None
+        );
+        let notice = notice_for(&receipt, &me.public_key(), Some(server.public_key())).unwrap();
+        assert_eq!(notice.kind, NoticeKind::Zap);
+        assert_eq!(notice.body, "⚡ 21 sats");
+        assert_eq!(notice.path, note_path(mine.id));
+    }
+
+    #[test]
+    fn own_and_untagged_events_are_ignored() {
+        let me = Keys::generate();
+        let mine = EventBuilder::text_note("hi", [Tag::public_key(me.public_key())])
+            .to_event(&me)
+            .unwrap();
+        assert_eq!(notice_for(&mine, &me.public_key(), None), None);
+        let other = EventBuilder::text_note("hi", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(notice_for(&other, &me.public_key(), None), None);
+    }
+
+    #[test]
+    fn long_notes_are_cut() {
+        let text = "ก".repeat(BODY_CHARS + 5);
+        let body = excerpt(&text);
+        assert_eq!(body.chars().count(), BODY_CHARS + 1);
+        assert!(body.ends_with('…'));
+    }
+}
@@ -0,0 +1,677 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::async_utility::futures_util::future::join;
+use nostr_sdk::async_utility::thread::sleep;
+use nostr_sdk::prelude::*;
+
+use crate::helper::address::{address_identifier, coordinate_filter, matches_coordinate};
+use crate::helper::store::EventStore;
+use crate::helper::validate::Validator;
+
+/// Extra relays, on top of the user's own, the pool may hold at once.
+pub const MAX_OUTBOX_RELAYS: usize = 8;
+/// Relays asked per author, NIP-65 suggests a few rather than all of them.
+pub const RELAYS_PER_AUTHOR: usize = 3;
+
+/// Hinted relays taken from one bech32 entity, links may list many.
+pub const MAX_HINTS: usize = 3;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_POLL: Duration = Duration::from_millis(200);
+
+/// An author's kind-10002. A bare `r` tag is both read and write.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayList {
+    pub read: Vec<Url>,
+    pub write: Vec<Url>,
+}
+
+impl RelayList {
+    pub fn from_event(event: &Event) -> Self {
+        let mut list = Self::default();
+        for tag in event.iter_tags() {
+            let (url, marker) = match tag.as_vec() {
+                [kind, url] if kind == "r" => (url, None),
+                [kind, url, marker, ..] if kind == "r" => (url, Some(marker.as_str())),
+                _ => continue,
+            };
+            let Ok(url) = Url::parse(url.trim()) else {
+                continue;
+            };
+            if !matches!(url.scheme(), "ws" | "wss") {
+                continue;
+            }
+            if marker != Some("write") && !list.read.contains(&url) {
+                list.read.push(url.clone());
+            }
+            if marker != Some("read") && !list.write.contains(&url) {
+                list.write.push(url);
+            }
+        }
+        list
+    }
+}
+
+/// Relay hints of an `nevent`, `nprofile` or `naddr` worth connecting to:
+/// websocket URLs, deduplicated, at most [`MAX_HINTS`].
+pub fn hint_urls(hints: &[String]) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for url in hints.iter().filter_map(|hint| Url::parse(hint.trim()).ok()) {
+        if urls.len() == MAX_HINTS {
+            break;
+        }
+        if matches!(url.scheme(), "ws" | "wss") && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Up to [`RELAYS_PER_AUTHOR`] relays from each list, preferring ones already in
+/// the pool and then ones many authors share, with at most `max_new` relays
+/// that are not connected yet.
+pub fn pick_relays(lists: &[Vec<Url>], in_pool: &HashSet<Url>, max_new: usize) -> Vec<Url> {
+    let mut shared: HashMap<&Url, usize> = HashMap::new();
+    for url in lists.iter().flatten() {
+        *shared.entry(url).or_default() += 1;
+    }
+    let mut picked: Vec<Url> = Vec::new();
+    let mut new = 0;
+    for list in lists {
+        let mut ranked: Vec<&Url> = list.iter().collect();
+        // stable, so the author's own order breaks ties
+        ranked.sort_by_key(|url| (!in_pool.contains(*url), std::cmp::Reverse(shared[url])));
+        let mut taken = 0;
+        for url in ranked {
+            if taken == RELAYS_PER_AUTHOR {
+                break;
+            }
+            if picked.contains(url) {
+                taken += 1;
+                continue;
+            }
+            if !in_pool.contains(url) {
+                if new == max_new {
+                    continue;
+                }
+                new += 1;
+            }
+            picked.push(url.clone());
+            taken += 1;
+        }
+    }
+    picked
+}
+
+/// Answers from the user's pool and the routed one, each event once. Fails only
+/// when neither answered.
+fn merge(
+    mine: Result<Vec<Event>, String>,
+    routed: Result<Vec<Event>, String>,
+) -> Result<Vec<Event>, String> {
+    match (mine, routed) {
+        (Err(mine), Err(routed)) => Err(format!("{}, {}", mine, routed)),
+        (mine, routed) => {
+            let mut seen = HashSet::new();
+            Ok(mine
+                .into_iter()
+                .chain(routed)
+                .flatten()
+                .filter(|event| seen.insert(event.id))
+                .collect())
+        }
+    }
+}
+
+/// NIP-65 routing shared through context: kind-10002 lists are fetched once per
+/// author and kept in the store, and their relays are joined on demand. Joined
+/// and hinted relays live in a pool of their own, so the subscriptions of the
+/// user's pool (feed, reactions, notifications) are not copied to them; pages
+/// ask them through [`Outbox::fetch`], [`Outbox::fetch_from`] and
+/// [`Outbox::subscribe_to`]. Joined relays are evicted oldest first past
+/// [`MAX_OUTBOX_RELAYS`]. Relay hints from shared links are held by the pages
+/// that use them and dropped once the last one lets go.
+#[derive(Clone, Copy)]
+pub struct Outbox {
+    store: EventStore,
+    validator: Validator,
+    client: RwSignal<Client>,
+    /// Pool of joined and hinted relays
+    routed: RwSignal<Client>,
+    fetched: StoredValue<HashSet<PublicKey>>,
+    /// Relays joined for routing, least recently used first
+    joined: StoredValue<VecDeque<Url>>,
+    /// Held hint relays: how many pages hold each and whether we added it
+    hinted: StoredValue<HashMap<Url, (usize, bool)>>,
+}
+
+impl Outbox {
+    pub fn new(client: RwSignal<Client>, store: EventStore, validator: Validator) -> Self {
+        Self {
+            store,
+            validator,
+            client,
+            routed: create_rw_signal(Client::default()),
+            fetched: store_value(HashSet::new()),
+            joined: store_value(VecDeque::new()),
+            hinted: store_value(HashMap::new()),
+        }
+    }
+
+    /// Relay list of `author`, fetched from the user's relays the first time a
+    /// relay answers.
+    pub async fn relay_list(&self, author: PublicKey) -> Option<RelayList> {
+        let first = self
+            .fetched
+            .try_update_value(|set| set.insert(author))
+            .unwrap_or(false);
+        if first {
+            let client = self.client.get_untracked();
+            let filter = Filter::new().author(author).kind(Kind::RelayList);
+            match self
+                .validator
+                .fetch(&client, None, vec![filter], FETCH_TIMEOUT)
+                .await
+            {
+                Ok(events) => events
+                    .into_iter()
+                    .filter(|event| event.pubkey == author)
+                    .for_each(|event| {
+                        self.store.insert(event);
+                    }),
+                Err(e) => {
+                    log!("relay list of {} failed: {}", author, e);
+                    self.fetched.try_update_value(|set| {
+                        set.remove(&author);
+                    });
+                }
+            }
+        }
+        self.store
+            .replaceable_untracked(Kind::RelayList, author, None)
+            .map(|event| RelayList::from_event(&event))
+    }
+
+    /// Connected relays where `authors` publish. Empty when none of them has a
+    /// relay list, callers then stay on the user's relays.
+    pub async fn write_relays(&self, authors: &[PublicKey]) -> Vec<Url> {
+        let mut lists = Vec::new();
+        for author in authors {
+            if let Some(list) = self.relay_list(*author).await {
+                lists.push(list.write);
+            }
+        }
+        self.join(&lists).await
+    }
+
+    /// Connected relays where `author` reads, so replies and mentions land there.
+    pub async fn read_relays(&self, author: PublicKey) -> Vec<Url> {
+        match self.relay_list(author).await {
+            Some(list) => self.join(&[list.read]).await,
+            None => Vec::new(),
+        }
+    }
+
+    /// The user's relays and `extra`, relays returned by this outbox.
+    pub async fn fetch(
+        &self,
+        extra: &[Url],
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, String> {
+        let client = self.client.get_untracked();
+        let routed = self.routed.get_untracked();
+        let (mine, theirs) = join(
+            self.validator
+                .fetch(&client, None, filters.clone(), timeout),
+            self.validator.fetch(&routed, Some(extra), filters, timeout),
+        )
+        .await;
+        merge(mine, theirs)
+    }
+
+    /// Only `urls`, from whichever pool holds them.
+    pub async fn fetch_from(
+        &self,
+        urls: &[Url],
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, String> {
+        let client = self.client.get_untracked();
+        let routed = self.routed.get_untracked();
+        let (mine, theirs) = join(
+            self.validator
+                .fetch(&client, Some(urls), filters.clone(), timeout),
+            self.validator.fetch(&routed, Some(urls), filters, timeout),
+        )
+        .await;
+        merge(mine, theirs)
+    }
+
+    /// Live subscription on the routed relays among `urls`. The user's relays
+    /// need their own subscription.
+    pub async fn subscribe_to(&self, urls: &[Url], id: SubscriptionId, filters: Vec<Filter>) {
+        let routed = self.routed.get_untracked();
+        let in_pool: HashSet<Url> = routed.relays().await.into_keys().collect();
+        let urls: Vec<&Url> = urls.iter().filter(|url| in_pool.contains(*url)).collect();
+        if urls.is_empty() {
+            return;
+        }
+        if let Err(e) = routed.subscribe_with_id_to(urls, id, filters, None).await {
+            log!("routed subscription failed: {}", e);
+        }
+    }
+
+    /// Pool of the joined and hinted relays, listen to its notifications for
+    /// [`Outbox::subscribe_to`].
+    pub fn routed(&self) -> Client {
+        self.routed.get_untracked()
+    }
+
+    pub async fn unsubscribe(&self, id: SubscriptionId) {
+        self.routed.get_untracked().unsubscribe(id).await;
+    }
+
+    /// `id` from the store, or from relays when it was never seen: the `hinted`
+    /// relays first, then the user's, then the author's write relays when known.
+    pub async fn fetch_event(
+        &self,
+        id: EventId,
+        author: Option<PublicKey>,
+        hinted: Vec<Url>,
+    ) -> Option<Event> {
+        if let Some(event) = self.store.get_untracked(&id) {
+            return Some(event);
+        }
+        let client = self.client.get_untracked();
+        let filters = vec![Filter::new().id(id)];
+        let mut found = Vec::new();
+        if !hinted.is_empty() {
+            match self
+                .fetch_from(&hinted, filters.clone(), FETCH_TIMEOUT)
+                .await
+            {
+                Ok(events) => found = events,
+                Err(e) => log!("fetch {} from hints failed: {}", id, e),
+            }
+        }
+        if found.is_empty() {
+            match self
+                .validator
+                .fetch(&client, None, filters.clone(), FETCH_TIMEOUT)
+                .await
+            {
+                Ok(events) => found = events,
+                Err(e) => log!("fetch {} failed: {}", id, e),
+            }
+        }
+        if found.is_empty() {
+            let relays = match author {
+                Some(author) => self.write_relays(&[author]).await,
+                None => Vec::new(),
+            };
+            if !relays.is_empty() {
+                match self.fetch_from(&relays, filters, FETCH_TIMEOUT).await {
+                    Ok(events) => found = events,
+                    Err(e) => log!("fetch {} from outbox failed: {}", id, e),
+                }
+            }
+        }
+        let event = found.into_iter().find(|event| event.id == id)?;
+        self.store.insert(event.clone());
+        Some(event)
+    }
+
+    /// Newest version of the `coordinate` into the store: the `hinted` relays
+    /// first, then the author's write relays and the user's.
+    pub async fn fetch_address(&self, coordinate: &Coordinate, hinted: Vec<Url>) -> Option<Event> {
+        let filters = vec![coordinate_filter(coordinate)];
+        let keep = |events: Vec<Event>| {
+            events
+                .into_iter()
+                .filter(|event| matches_coordinate(event, coordinate))
+                .fold(false, |_, event| {
+                    self.store.insert(event);
+                    true
+                })
+        };
+        let mut found = false;
+        if !hinted.is_empty() {
+            match self
+                .fetch_from(&hinted, filters.clone(), FETCH_TIMEOUT)
+                .await
+            {
+                Ok(events) => found = keep(events),
+                Err(e) => log!("naddr from hints failed: {}", e),
+            }
+        }
+        if !found {
+            let relays = self.write_relays(&[coordinate.public_key]).await;
+            match self.fetch(&relays, filters, FETCH_TIMEOUT).await {
+                Ok(events) => {
+                    keep(events);
+                }
+                Err(e) => log!("naddr fetch failed: {}", e),
+            }
+        }
+        self.store.replaceable_untracked(
+            coordinate.kind,
+            coordinate.public_key,
+            address_identifier(coordinate),
+        )
+    }
+
+    /// Hold the relay hints of a bech32 entity until [`Outbox::release_hints`].
+    /// Returns the held relays, connect them with [`Outbox::connect_hints`].
+    pub fn hold_hints(&self, hints: &[String]) -> Vec<Url> {
+        let urls = hint_urls(hints);
+        let _ = self.hinted.try_update_value(|hinted| {
+            for url in &urls {
+                hinted.entry(url.clone()).or_insert((0, false)).0 += 1;
+            }
+        });
+        urls
+    }
+
+    /// Add the held `urls` missing from both pools to the routed one, and
+    /// return the ones that connected.
+    pub async fn connect_hints(&self, urls: &[Url]) -> Vec<Url> {
+        let routed = self.routed.get_untracked();
+        let in_pool = self.in_pools().await;
+        for url in urls.iter().filter(|url| !in_pool.contains(*url)) {
+            // released already, or another page is adding it
+            let add = self
+                .hinted
+                .try_update_value(|hinted| match hinted.get_mut(url) {
+                    Some((count, added)) if *count > 0 && !*added => {
+                        *added = true;
+                        true
+                    }
+                    _ => false,
+                })
+                .unwrap_or(false);
+            if !add {
+                continue;
+            }
+            let opts = RelayOptions::new().flags(RelayServiceFlags::READ | RelayServiceFlags::PING);
+            if let Err(e) = routed.add_relay_with_opts(url.clone(), opts).await {
+                log!("add hinted relay {}: {}", url, e);
+                continue;
+            }
+            if let Err(e) = routed.connect_relay(url.clone()).await {
+                log!("connect hinted relay {}: {}", url, e);
+            }
+        }
+        let mut ready = Vec::new();
+        for url in urls {
+            if self.wait_ready(url).await {
+                ready.push(url.clone());
+            }
+        }
+        ready
+    }
+
+    /// Let go of hints from [`Outbox::hold_hints`]. Relays we added for them
+    /// leave the routed pool with their last holder, unless the outbox joined
+    /// them by now.
+    pub fn release_hints(&self, urls: &[Url]) {
+        let dropped: Vec<Url> = self
+            .hinted
+            .try_update_value(|hinted| {
+                let mut dropped = Vec::new();
+                for url in urls {
+                    let Some((count, added)) = hinted.get_mut(url) else {
+                        continue;
+                    };
+                    *count = count.saturating_sub(1);
+                    if *count == 0 {
+                        if *added {
+                            dropped.push(url.clone());
+                        }
+                        hinted.remove(url);
+                    }
+                }
+                dropped
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|url| {
+                !self
+                    .joined
+                    .try_with_value(|joined| joined.contains(url))
+                    .unwrap_or(false)
+            })
+            .collect();
+        if dropped.is_empty() {
+            return;
+        }
+        let routed = self.routed.get_untracked();
+        spawn_local(async move {
+            for url in dropped {
+                if let Err(e) = routed.remove_relay(url.clone()).await {
+                    log!("remove hinted relay {}: {}", url, e);
+                }
+            }
+        });
+    }
+
+    async fn join(&self, lists: &[Vec<Url>]) -> Vec<Url> {
+        let routed = self.routed.get_untracked();
+        let in_routed: HashSet<Url> = routed.relays().await.into_keys().collect();
+        let in_pool = self.in_pools().await;
+        let picked = pick_relays(lists, &in_pool, MAX_OUTBOX_RELAYS);
+        let _ = self.joined.try_update_value(|joined| {
+            // used again, move to the back of the queue
+            joined.retain(|url| !picked.contains(url));
+            joined.extend(
+                picked
+                    .iter()
+                    .filter(|url| in_routed.contains(*url))
+                    .cloned(),
+            );
+        });
+        for url in picked.iter().filter(|url| !in_pool.contains(*url)) {
+            self.evict_one(&routed, &picked).await;
+            // read only, the user's notes keep going to the relays they chose
+            let opts = RelayOptions::new().flags(RelayServiceFlags::READ | RelayServiceFlags::PING);
+            if let Err(e) = routed.add_relay_with_opts(url.clone(), opts).await {
+                log!("add outbox relay {}: {}", url, e);
+                continue;
+            }
+            let _ = self
+                .joined
+                .try_update_value(|joined| joined.push_back(url.clone()));
+            if let Err(e) = routed.connect_relay(url.clone()).await {
+                log!("connect outbox relay {}: {}", url, e);
+            }
+        }
+        let mut ready = Vec::new();
+        for url in picked {
+            if self.wait_ready(&url).await {
+                ready.push(url);
+            }
+        }
+        ready
+    }
+
+    /// Relays of the user's pool and the routed one.
+    async fn in_pools(&self) -> HashSet<Url> {
+        let mut urls: HashSet<Url> = self
+            .client
+            .get_untracked()
+            .relays()
+            .await
+            .into_keys()
+            .collect();
+        urls.extend(self.routed.get_untracked().relays().await.into_keys());
+        urls
+    }
+
+    /// Whichever pool holds `url` has it connected.
+    async fn wait_ready(&self, url: &Url) -> bool {
+        wait_connected(&self.routed.get_untracked(), url).await
+            || wait_connected(&self.client.get_untracked(), url).await
+    }
+
+    /// Drop the least recently used joined relay outside `keep` from `routed`
+    /// once the cap is reached.
+    async fn evict_one(&self, routed: &Client, keep: &[Url]) {
+        let in_pool: HashSet<Url> = routed.relays().await.into_keys().collect();
+        let oldest = self
+            .joined
+            .try_update_value(|joined| {
+                // hints released while joined may already have removed some
+                joined.retain(|url| in_pool.contains(url));
+                if joined.len() < MAX_OUTBOX_RELAYS {
+                    return None;
+                }
+                let held = |url: &Url| {
+                    self.hinted
+                        .try_with_value(|hinted| hinted.contains_key(url))
+                        .unwrap_or(false)
+                };
+                let at = joined
+                    .iter()
+                    .position(|url| !keep.contains(url) && !held(url))?;
+                joined.remove(at)
+            })
+            .flatten();
+        if let Some(url) = oldest {
+            if let Err(e) = routed.remove_relay(url.clone()).await {
+                log!("remove outbox relay {}: {}", url, e);
+            }
+        }
+    }
+}
+
+async fn wait_connected(client: &Client, url: &Url) -> bool {
+    let Ok(relay) = client.relay(url.clone()).await else {
+        return false;
+    };
+    let mut waited = Duration::ZERO;
+    while waited < CONNECT_TIMEOUT {
+        if relay.status().await == RelayStatus::Connected {
+            return true;
+        }
+        sleep(CONNECT_POLL).await;
+        waited += CONNECT_POLL;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn read_and_write_markers() {
+        let tags = [
+            vec!["r", "wss://both.example"],
+            vec!["r", "wss://inbox.example", "read"],
+            vec!["r", "wss://outbox.example", "write"],
+            vec!["r", "https://not-a-relay.example"],
+            vec!["p", "wss://ignored.example"],
+        ]
+        .iter()
+        .map(|t| Tag::parse(t).unwrap())
+        .collect::<Vec<_>>();
+        let event = EventBuilder::new(Kind::RelayList, "", tags)
+            .to_event(&Keys::generate())
+            .unwrap();
+        let list = RelayList::from_event(&event);
+        assert_eq!(
+            list.read,
+            vec![url("wss://both.example"), url("wss://inbox.example")]
+        );
+        assert_eq!(
+            list.write,
+            vec![url("wss://both.example"), url("wss://outbox.example")]
+        );
+    }
+
+    #[test]
+    fn prefers_connected_and_shared_relays() {
+        let pool = url("wss://pool.example");
+        let shared = url("wss://shared.example");
+        let lists = vec![
+            vec![
+                url("wss://a1.example"),
+                url("wss://a2.example"),
+                shared.clone(),
+                pool.clone(),
+            ],
+            vec![shared.clone(), url("wss://b1.example")],
+        ];
+        let in_pool = HashSet::from([pool.clone()]);
+        assert_eq!(
+            pick_relays(&lists, &in_pool, 8),
+            vec![
+                pool.clone(),
+                shared.clone(),
+                url("wss://a1.example"),
+                url("wss://b1.example"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hints_are_websockets_and_capped() {
+        let hints = [
+            "wss://a.example",
+            "https://web.example",
+            "wss://a.example",
+            "not a url",
+            "ws://b.example",
+            "wss://c.example",
+            "wss://d.example",
+        ]
+        .map(String::from);
+        assert_eq!(
+            hint_urls(&hints),
+            vec![
+                url("wss://a.example"),
+                url("ws://b.example"),
+                url("wss://c.example")
+            ]
+        );
+    }
+
+    #[test]
+    fn new_connections_are_capped() {
+        let lists = vec![
+            vec![url("wss://a.example"), url("wss://b.example")],
+            vec![url("wss://c.example"), url("wss://pool.example")],
+        ];
+        let in_pool = HashSet::from([url("wss://pool.example")]);
+        assert_eq!(
+            pick_relays(&lists, &in_pool, 1),
+            vec![url("wss://a.example"), url("wss://pool.example")]
+        );
+    }
+
+    #[test]
+    fn both_pools_are_merged() {
+        let keys = Keys::generate();
+        let note = |content: &str| {
+            EventBuilder::text_note(content, [])
+                .to_event(&keys)
+                .unwrap()
+        };
+        let (a, b) = (note("a"), note("b"));
+        let merged = merge(Ok(vec![a.clone()]), Ok(vec![a.clone(), b.clone()])).unwrap();
+        assert_eq!(merged, vec![a.clone(), b]);
+        assert_eq!(
+            merge(Err("timeout".to_string()), Ok(vec![a.clone()])),
+            Ok(vec![a])
+        );
+        assert!(merge(Err("x".to_string()), Err("y".to_string())).is_err());
+    }
+}
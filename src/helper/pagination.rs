@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use nostr_sdk::prelude::*;
+
+/// Notes asked for per relay and page.
+pub const PAGE_SIZE: usize = 50;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct RelayCursor {
+    oldest: Option<Timestamp>,
+    received: usize,
+    in_flight: bool,
+    exhausted: bool,
+}
+
+/// How far back each relay has been read. A relay is exhausted once a page ends
+/// (EOSE) without a single note, every relay keeps its own `until` cursor. One
+/// that never sent a note has no cursor to page from and counts as exhausted too.
+#[derive(Clone, Debug, Default)]
+pub struct FeedHistory {
+    relays: HashMap<Url, RelayCursor>,
+}
+
+impl FeedHistory {
+    /// The first page was asked from `relays`.
+    pub fn start(&mut self, relays: impl IntoIterator<Item = Url>) {
+        for url in relays {
+            let cursor = self.relays.entry(url).or_default();
+            cursor.in_flight = true;
+            cursor.received = 0;
+        }
+    }
+
+    pub fn on_event(&mut self, relay: &Url, created_at: Timestamp) {
+        let cursor = self.relays.entry(relay.clone()).or_default();
+        cursor.received += 1;
+        cursor.oldest = Some(cursor.oldest.map_or(created_at, |t| t.min(created_at)));
+    }
+
+    pub fn on_eose(&mut self, relay: &Url) {
+        if let Some(cursor) = self.relays.get_mut(relay) {
+            // a late EOSE for an expired first page still ends an empty relay
+            if (cursor.in_flight && cursor.received == 0) || cursor.oldest.is_none() {
+                cursor.exhausted = true;
+            }
+            cursor.in_flight = false;
+        }
+    }
+
+    /// Give up on relays that never answered, they are asked again next page.
+    pub fn expire(&mut self) {
+        for cursor in self.relays.values_mut() {
+            cursor.in_flight = false;
+        }
+    }
+
+    /// `until` per relay for the next page, marking them in flight. Empty while
+    /// a page is still loading or history is exhausted.
+    pub fn next_page(&mut self) -> Vec<(Url, Timestamp)> {
+        if self.is_loading() {
+            return Vec::new();
+        }
+        self.relays
+            .iter_mut()
+            .filter(|(_, cursor)| !cursor.exhausted)
+            .filter_map(|(url, cursor)| {
+                let Some(oldest) = cursor.oldest else {
+                    // the first page came back empty or never came
+                    cursor.exhausted = true;
+                    return None;
+                };
+                let until = oldest.as_u64().checked_sub(1)?;
+                cursor.in_flight = true;
+                cursor.received = 0;
+                Some((url.clone(), Timestamp::from(until)))
+            })
+            .collect()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.relays.values().any(|cursor| cursor.in_flight)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        !self.relays.is_empty() && self.relays.values().all(|cursor| cursor.exhausted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn every_relay_pages_from_its_own_oldest_note() {
+        let (a, b) = (url("wss://a.example"), url("wss://b.example"));
+        let mut history = FeedHistory::default();
+        history.start([a.clone(), b.clone()]);
+        history.on_event(&a, Timestamp::from(100));
+        history.on_event(&a, Timestamp::from(90));
+        history.on_event(&b, Timestamp::from(500));
+        assert!(history.next_page().is_empty(), "first page still loading");
+
+        history.on_eose(&a);
+        history.on_eose(&b);
+        assert!(!history.is_loading());
+        let mut page = history.next_page();
+        page.sort();
+        assert_eq!(
+            page,
+            vec![
+                (a.clone(), Timestamp::from(89)),
+                (b.clone(), Timestamp::from(499))
+            ]
+        );
+        assert!(history.is_loading());
+    }
+
+    #[test]
+    fn empty_page_exhausts_relay() {
+        let (a, b) = (url("wss://a.example"), url("wss://b.example"));
+        let mut history = FeedHistory::default();
+        history.start([a.clone(), b.clone()]);
+        history.on_event(&a, Timestamp::from(100));
+        history.on_eose(&a);
+        history.on_eose(&b);
+        assert!(!history.is_exhausted());
+
+        assert_eq!(history.next_page(), vec![(a.clone(), Timestamp::from(99))]);
+        history.on_eose(&a);
+        assert!(history.is_exhausted());
+        assert!(history.next_page().is_empty());
+    }
+
+    #[test]
+    fn unanswered_relays_are_retried() {
+        let a = url("wss://a.example");
+        let mut history = FeedHistory::default();
+        history.start([a.clone()]);
+        history.on_event(&a, Timestamp::from(100));
+        assert!(history.is_loading());
+        history.expire();
+        assert!(!history.is_exhausted());
+        assert_eq!(history.next_page(), vec![(a, Timestamp::from(99))]);
+    }
+
+    #[test]
+    fn relays_without_notes_do_not_block_exhaustion() {
+        let (a, b) = (url("wss://a.example"), url("wss://b.example"));
+        let mut history = FeedHistory::default();
+        history.start([a.clone(), b.clone()]);
+        history.on_event(&a, Timestamp::from(100));
+        history.on_eose(&a);
+        // `b` stays silent past the page timeout
+        history.expire();
+        assert_eq!(history.next_page(), vec![(a.clone(), Timestamp::from(99))]);
+        history.on_eose(&a);
+        assert!(history.is_exhausted());
+
+        // or its empty first page ends after the timeout
+        let mut history = FeedHistory::default();
+        history.start([b.clone()]);
+        history.expire();
+        history.on_eose(&b);
+        assert!(history.is_exhausted());
+    }
+}
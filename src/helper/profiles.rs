@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::validate::Validator;
+
+const BATCH_DELAY: Duration = Duration::from_millis(300);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// First retry of a pubkey that came back without a kind-0, doubled each time.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Newest kind-0 seen for a pubkey.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub metadata: Metadata,
+    pub created_at: Timestamp,
+}
+
+impl Profile {
+    pub fn name(&self) -> Option<String> {
+        self.metadata
+            .display_name
+            .clone()
+            .or_else(|| self.metadata.name.clone())
+            .filter(|name| !name.trim().is_empty())
+    }
+}
+
+pub fn short_npub(public_key: &PublicKey) -> String {
+    let npub = public_key.to_bech32().unwrap_or_default();
+    format!(
+        "{}…{}",
+        npub.get(..10).unwrap_or_default(),
+        npub.get(npub.len().saturating_sub(4)..).unwrap_or_default()
+    )
+}
+
+/// `/p/<npub>` link for an author.
+pub fn profile_path(public_key: &PublicKey) -> String {
+    format!("/p/{}", public_key.to_bech32().unwrap_or_default())
+}
+
+/// Website field as a link, people often leave out the scheme.
+pub fn website_url(website: &str) -> Option<String> {
+    let website = website.trim();
+    let with_scheme = if website.contains("://") {
+        website.to_string()
+    } else {
+        format!("https://{}", website)
+    };
+    let url = Url::parse(&with_scheme).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Distinct pubkeys followed in a kind-3.
+pub fn following_count(contacts: &Event) -> usize {
+    contacts.public_keys().collect::<HashSet<_>>().len()
+}
+
+/// Distinct authors among kind-3 lists that follow `author`.
+pub fn follower_count(contact_lists: &[Event], author: &PublicKey) -> usize {
+    contact_lists
+        .iter()
+        .filter(|event| {
+            event.kind == Kind::ContactList && event.public_keys().any(|pk| pk == author)
+        })
+        .map(|event| event.pubkey)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Pubkeys waiting for the next batch, and every one asked for so far.
+#[derive(Default)]
+struct FetchQueue {
+    requested: HashSet<PublicKey>,
+    queue: Vec<PublicKey>,
+    attempts: HashMap<PublicKey, u32>,
+}
+
+impl FetchQueue {
+    /// `true` when `public_key` starts a new batch.
+    fn push(&mut self, public_key: PublicKey) -> bool {
+        if !self.requested.insert(public_key) {
+            return false;
+        }
+        self.queue.push(public_key);
+        self.queue.len() == 1
+    }
+
+    fn take(&mut self) -> Vec<PublicKey> {
+        std::mem::take(&mut self.queue)
+    }
+
+    /// Members of `batch` missing from `found` that deserve another try, and
+    /// how long to wait first. Those past [`MAX_ATTEMPTS`] stay requested.
+    fn missed(
+        &mut self,
+        batch: &[PublicKey],
+        found: &HashSet<PublicKey>,
+    ) -> (Vec<PublicKey>, Duration) {
+        let mut retry = Vec::new();
+        let mut attempts = 0;
+        for public_key in batch {
+            if found.contains(public_key) {
+                self.attempts.remove(public_key);
+                continue;
+            }
+            let tried = self.attempts.entry(*public_key).or_default();
+            *tried += 1;
+            if *tried < MAX_ATTEMPTS {
+                attempts = attempts.max(*tried);
+                self.requested.remove(public_key);
+                retry.push(*public_key);
+            }
+        }
+        (retry, RETRY_DELAY * 2_u32.pow(attempts.saturating_sub(1)))
+    }
+}
+
+/// Kind-0 cache shared through context. Pubkeys asked for within [`BATCH_DELAY`]
+/// are fetched together in a single filter.
+#[derive(Clone, Copy)]
+pub struct ProfileCache {
+    profiles: RwSignal<HashMap<PublicKey, Profile>>,
+    queue: StoredValue<FetchQueue>,
+    client: RwSignal<Client>,
+    validator: Validator,
+}
+
+impl ProfileCache {
+    pub fn new(client: RwSignal<Client>, validator: Validator) -> Self {
+        Self {
+            profiles: create_rw_signal(HashMap::new()),
+            queue: store_value(FetchQueue::default()),
+            client,
+            validator,
+        }
+    }
+
+    /// Tracked lookup, queues a fetch the first time a pubkey is seen.
+    pub fn get(&self, public_key: &PublicKey) -> Option<Profile> {
+        self.request(*public_key);
+        self.profiles.with(|map| map.get(public_key).cloned())
+    }
+
+    /// Untracked, cached pubkeys whose profile passes `keep`.
+    pub fn find(&self, keep: impl Fn(&Profile) -> bool) -> Vec<PublicKey> {
+        self.profiles.with_untracked(|map| {
+            map.iter()
+                .filter(|(_, profile)| keep(profile))
+                .map(|(public_key, _)| *public_key)
+                .collect()
+        })
+    }
+
+    pub fn request(&self, public_key: PublicKey) {
+        let first_in_batch = self
+            .queue
+            .try_update_value(|queue| queue.push(public_key))
+            .unwrap_or(false);
+        if first_in_batch {
+            let cache = *self;
+            set_timeout(move || cache.flush(), BATCH_DELAY);
+        }
+    }
+
+    /// Keep `event` if it is a kind-0 newer than what we hold.
+    pub fn insert(&self, event: &Event) {
+        if event.kind != Kind::Metadata {
+            return;
+        }
+        let newer = self.profiles.with_untracked(|map| {
+            map.get(&event.pubkey)
+                .is_none_or(|p| p.created_at < event.created_at)
+        });
+        if !newer {
+            return;
+        }
+        match Metadata::from_json(event.content()) {
+            Ok(metadata) => self.profiles.update(|map| {
+                map.insert(
+                    event.pubkey,
+                    Profile {
+                        metadata,
+                        created_at: event.created_at,
+                    },
+                );
+            }),
+            Err(e) => log!("bad metadata from {}: {}", event.pubkey, e),
+        }
+    }
+
+    fn flush(&self) {
+        let batch = self
+            .queue
+            .try_update_value(FetchQueue::take)
+            .unwrap_or_default();
+        if batch.is_empty() {
+            return;
+        }
+        let cache = *self;
+        let client = self.client.get_untracked();
+        spawn_local(async move {
+            let filter = Filter::new().authors(batch.clone()).kind(Kind::Metadata);
+            let found = match cache
+                .validator
+                .fetch(&client, None, vec![filter], FETCH_TIMEOUT)
+                .await
+            {
+                Ok(events) => events
+                    .iter()
+                    .map(|event| {
+                        cache.insert(event);
+                        event.pubkey
+                    })
+                    .collect(),
+                Err(e) => {
+                    log!("metadata fetch failed: {}", e);
+                    HashSet::new()
+                }
+            };
+            cache.retry(&batch, &found);
+        });
+    }
+
+    /// A timeout or an empty answer should not hide a profile for the session.
+    fn retry(&self, batch: &[PublicKey], found: &HashSet<PublicKey>) {
+        let Some((missed, delay)) = self
+            .queue
+            .try_update_value(|queue| queue.missed(batch, found))
+        else {
+            return;
+        };
+        if missed.is_empty() {
+            return;
+        }
+        let cache = *self;
+        set_timeout(
+            move || {
+                missed
+                    .into_iter()
+                    .for_each(|public_key| cache.request(public_key))
+            },
+            delay,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contacts(keys: &Keys, follows: &[PublicKey]) -> Event {
+        EventBuilder::new(
+            Kind::ContactList,
+            "",
+            follows.iter().map(|pk| Tag::public_key(*pk)),
+        )
+        .to_event(keys)
+        .unwrap()
+    }
+
+    #[test]
+    fn pubkeys_are_batched_and_retried() {
+        let (a, b) = (Keys::generate().public_key(), Keys::generate().public_key());
+        let mut queue = FetchQueue::default();
+        assert!(queue.push(a));
+        assert!(!queue.push(b));
+        assert!(!queue.push(a));
+        let batch = queue.take();
+        assert_eq!(batch, vec![a, b]);
+
+        let (retry, delay) = queue.missed(&batch, &HashSet::from([a]));
+        assert_eq!((retry, delay), (vec![b], RETRY_DELAY));
+        assert!(!queue.push(a));
+        assert!(queue.push(b));
+        let batch = queue.take();
+        let (retry, delay) = queue.missed(&batch, &HashSet::new());
+        assert_eq!((retry, delay), (vec![b], RETRY_DELAY * 2));
+        queue.push(b);
+        let batch = queue.take();
+        let (retry, _) = queue.missed(&batch, &HashSet::new());
+        assert!(retry.is_empty());
+        assert!(!queue.push(b));
+    }
+
+    #[test]
+    fn newest_metadata_wins() {
+        let runtime = create_runtime();
+        let cache = ProfileCache::new(create_rw_signal(Client::default()), Validator::new());
+        let keys = Keys::generate();
+        let metadata = |name: &str, at: u64| {
+            EventBuilder::metadata(&Metadata::new().name(name))
+                .custom_created_at(Timestamp::from(at))
+                .to_event(&keys)
+                .unwrap()
+        };
+        cache.insert(&metadata("new", 20));
+        cache.insert(&metadata("old", 10));
+        let name = |cache: &ProfileCache| {
+            cache
+                .profiles
+                .with_untracked(|map| map.get(&keys.public_key()).and_then(Profile::name))
+        };
+        assert_eq!(name(&cache).as_deref(), Some("new"));
+        cache.insert(&metadata("newer", 30));
+        assert_eq!(name(&cache).as_deref(), Some("newer"));
+        runtime.dispose();
+    }
+
+    #[test]
+    fn short_npub_keeps_both_ends() {
+        let keys = Keys::generate();
+        let npub = keys.public_key().to_bech32().unwrap();
+        let short = short_npub(&keys.public_key());
+        assert!(short.starts_with(&npub[..10]));
+        assert!(short.ends_with(&npub[npub.len() - 4..]));
+    }
+
+    #[test]
+    fn website_gets_a_scheme() {
+        assert_eq!(
+            website_url("siamstr.com").as_deref(),
+            Some("https://siamstr.com/")
+        );
+        assert_eq!(
+            website_url("http://example.com/me").as_deref(),
+            Some("http://example.com/me")
+        );
+        assert_eq!(website_url("javascript:alert(1)"), None);
+        assert_eq!(website_url(""), None);
+    }
+
+    #[test]
+    fn counts_distinct_follows_and_followers() {
+        let author = Keys::generate();
+        let other = Keys::generate().public_key();
+        let list = contacts(&author, &[other, other, Keys::generate().public_key()]);
+        assert_eq!(following_count(&list), 2);
+
+        let fan = Keys::generate();
+        let lists = vec![
+            contacts(&fan, &[author.public_key()]),
+            contacts(&fan, &[author.public_key(), other]),
+            contacts(&Keys::generate(), &[author.public_key()]),
+            contacts(&Keys::generate(), &[other]),
+        ];
+        assert_eq!(follower_count(&lists, &author.public_key()), 2);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use nostr_sdk::prelude::*;
+
+pub const FEED_HASHTAG: &str = "siamstr";
+
+/// Delivery state of a note written from this tab; sent notes are dropped from the map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublishState {
+    Pending,
+    Failed(String),
+}
+
+/// Record the outcome of sending note `id`: sent notes leave `pending`, failed
+/// ones stay with the relay error.
+pub fn settle(
+    pending: &mut HashMap<EventId, PublishState>,
+    id: EventId,
+    result: Result<EventId, String>,
+) {
+    match result {
+        Ok(_) => {
+            pending.remove(&id);
+        }
+        Err(e) => {
+            pending.insert(id, PublishState::Failed(e));
+        }
+    }
+}
+
+/// Tags of a `/t/:tag` route, `siamstr,thai` asks for either. Lowercased, without
+/// `#`, and [`FEED_HASHTAG`] when nothing usable is left.
+pub fn feed_tags(param: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in param.split(',') {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.is_empty() {
+        tags.push(FEED_HASHTAG.to_string());
+    }
+    tags
+}
+
+/// Build a kind-1 note tagged with `#siamstr`, the tags of the feed it was written
+/// in, plus any hashtag written in the content.
+pub fn siamstr_note(content: &str, feed_tags: &[String]) -> EventBuilder {
+    let mut hashtags = vec![FEED_HASHTAG.to_string()];
+    for tag in feed_tags {
+        if !hashtags.contains(tag) {
+            hashtags.push(tag.clone());
+        }
+    }
+    for word in content.split_whitespace() {
+        if let Some(tag) = word.strip_prefix('#') {
+            let tag = tag
+                .trim_end_matches(|c: char| c.is_ascii_punctuation())
+                .to_lowercase();
+            if !tag.is_empty() && !hashtags.contains(&tag) {
+                hashtags.push(tag);
+            }
+        }
+    }
+    EventBuilder::text_note(content, hashtags.into_iter().map(Tag::hashtag))
+}
+
+/// Sign `builder` with the active identity, report it as pending, then send it to the write relays.
+pub async fn sign_and_publish(
+    client: &Client,
+    builder: EventBuilder,
+    on_signed: impl FnOnce(&Event),
+) -> Result<EventId, String> {
+    let event = client
+        .sign_event_builder(builder)
+        .await
+        .map_err(|e| e.to_string())?;
+    on_signed(&event);
+    client.send_event(event).await.map_err(|e| e.to_string())
+}
+
+/// [`sign_and_publish`] to `urls` only.
+pub async fn sign_and_publish_to(
+    client: &Client,
+    urls: Vec<Url>,
+    builder: EventBuilder,
+) -> Result<EventId, String> {
+    let event = client
+        .sign_event_builder(builder)
+        .await
+        .map_err(|e| e.to_string())?;
+    client
+        .send_event_to(urls, event)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_tags_are_normalized() {
+        assert_eq!(
+            feed_tags("Siamstr, #thai,,siamstr"),
+            vec!["siamstr", "thai"]
+        );
+        assert_eq!(feed_tags(" , "), vec![FEED_HASHTAG]);
+    }
+
+    #[test]
+    fn notes_carry_feed_and_content_tags() {
+        let event = siamstr_note("สวัสดี #Bitcoin!", &["thai".to_string()])
+            .to_event(&Keys::generate())
+            .unwrap();
+        let tags: Vec<&str> = event
+            .iter_tags()
+            .filter_map(|tag| match tag.as_vec() {
+                [kind, value] if kind == "t" => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tags, vec!["siamstr", "thai", "bitcoin"]);
+    }
+
+    #[test]
+    fn feed_hashtag_is_tagged_once() {
+        let event = siamstr_note("#siamstr #SiamStr สวัสดี", &[])
+            .to_event(&Keys::generate())
+            .unwrap();
+        let tags: Vec<Vec<String>> = event.iter_tags().map(|tag| tag.as_vec().to_vec()).collect();
+        assert_eq!(tags, vec![vec!["t".to_string(), FEED_HASHTAG.to_string()]]);
+    }
+
+    #[test]
+    fn failed_notes_stay_pending() {
+        let id = EventId::all_zeros();
+        let mut pending = HashMap::from([(id, PublishState::Pending)]);
+        settle(&mut pending, id, Err("blocked".to_string()));
+        assert_eq!(
+            pending.get(&id),
+            Some(&PublishState::Failed("blocked".to_string()))
+        );
+        settle(&mut pending, id, Ok(id));
+        assert!(pending.is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::helper::validate::Validator;
+use crate::helper::zap::{zap_receipt, ZapReceipt, Zappers};
+
+const BATCH_DELAY: Duration = Duration::from_millis(300);
+/// Stored reactions of a batch are read once, the request closes itself after.
+const BACKLOG_TIMEOUT: Duration = Duration::from_secs(10);
+/// Most recently requested notes that keep getting new reactions live.
+const LIVE_NOTES: usize = 200;
+
+/// What a kind-7 says, per NIP-25 and NIP-30.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReactionKey {
+    Like,
+    Dislike,
+    Emoji(String),
+    Custom { shortcode: String, url: String },
+}
+
+pub fn reaction_key(event: &Event) -> ReactionKey {
+    let content = event.content().trim();
+    match content {
+        "" | "+" => return ReactionKey::Like,
+        "-" => return ReactionKey::Dislike,
+        _ => (),
+    }
+    if let Some(shortcode) = content.strip_prefix(':').and_then(|c| c.strip_suffix(':')) {
+        let url = event.iter_tags().find_map(|tag| match tag.as_vec() {
+            [kind, code, url, ..] if kind == "emoji" && code == shortcode => Some(url.clone()),
+            _ => None,
+        });
+        if let Some(url) = url {
+            return ReactionKey::Custom {
+                shortcode: shortcode.to_string(),
+                url,
+            };
+        }
+    }
+    ReactionKey::Emoji(content.to_string())
+}
+
+/// The note a reaction is for: the last `e` tag.
+pub fn reacted_note(event: &Event) -> Option<EventId> {
+    event.event_ids().last().copied()
+}
+
+/// Counts per reaction, most used first. A reactor counts once per reaction
+/// however many kind-7s they sent.
+pub fn summarize<'a>(
+    reactions: impl Iterator<Item = &'a (PublicKey, ReactionKey)>,
+) -> Vec<(ReactionKey, usize)> {
+    let unique: HashSet<&(PublicKey, ReactionKey)> = reactions.collect();
+    let mut counts: HashMap<&ReactionKey, usize> = HashMap::new();
+    for (_, key) in unique {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut summary: Vec<(ReactionKey, usize)> =
+        counts.into_iter().map(|(k, n)| (k.clone(), n)).collect();
+    summary.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    summary
+}
+
+/// reaction event -> (reactor, reaction)
+type NoteReactions = HashMap<EventId, (PublicKey, ReactionKey)>;
+/// zap receipt -> what it claims, counted once its zapper checks out
+type NoteZaps = HashMap<EventId, ZapReceipt>;
+
+/// Reactions and zap receipts for every note on screen. Notes asked for within
+/// [`BATCH_DELAY`] share one self-closing request for what relays hold, and the
+/// last [`LIVE_NOTES`] share a single live subscription whose filter is replaced
+/// with every batch.
+#[derive(Clone, Copy)]
+pub struct ReactionStore {
+    reactions: RwSignal<HashMap<EventId, NoteReactions>>,
+    zaps: RwSignal<HashMap<EventId, NoteZaps>>,
+    requested: StoredValue<HashSet<EventId>>,
+    queue: StoredValue<Vec<EventId>>,
+    subscriptions: StoredValue<HashSet<SubscriptionId>>,
+    live: StoredValue<(SubscriptionId, VecDeque<EventId>)>,
+    client: RwSignal<Client>,
+    zappers: Zappers,
+}
+
+impl ReactionStore {
+    pub fn new(client: RwSignal<Client>, validator: Validator, zappers: Zappers) -> Self {
+        let live = SubscriptionId::generate();
+        let store = Self {
+            reactions: create_rw_signal(HashMap::new()),
+            zaps: create_rw_signal(HashMap::new()),
+            requested: store_value(HashSet::new()),
+            queue: store_value(Vec::new()),
+            subscriptions: store_value(HashSet::from([live.clone()])),
+            live: store_value((live, VecDeque::new())),
+            client,
+            zappers,
+        };
+        let notify_client = client.get_untracked();
+        spawn_local(async move {
+            let mut notifications = notify_client.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Event {
+                    relay_url,
+                    subscription_id,
+                    event,
+                } = notification
+                {
+                    let ours = store
+                        .subscriptions
+                        .with_value(|subs| subs.contains(&subscription_id));
+                    if ours && validator.check(&relay_url, &event) {
+                        store.insert(&event);
+                    }
+                }
+            }
+        });
+        store
+    }
+
+    /// Tracked summary for `note`, subscribing to its reactions on first use.
+    pub fn summary(&self, note: EventId) -> Vec<(ReactionKey, usize)> {
+        self.request(note);
+        self.reactions.with(|map| {
+            map.get(&note)
+                .map(|r| summarize(r.values()))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Reactions `public_key` already left on `note`.
+    pub fn reacted_by(&self, note: EventId, public_key: &PublicKey) -> HashSet<ReactionKey> {
+        self.reactions.with(|map| {
+            map.get(&note)
+                .map(|r| {
+                    r.values()
+                        .filter(|(pk, _)| pk == public_key)
+                        .map(|(_, key)| key.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Tracked zap count and total sats for `note`, from receipts signed by
+    /// the recipient's LNURL server.
+    pub fn zap_total(&self, note: EventId) -> (usize, u64) {
+        self.request(note);
+        let receipts: Vec<ZapReceipt> = self.zaps.with(|map| {
+            map.get(&note)
+                .map(|zaps| zaps.values().cloned().collect())
+                .unwrap_or_default()
+        });
+        receipts
+            .iter()
+            .filter(|receipt| self.zappers.verifies(receipt))
+            .fold((0, 0), |(count, sats), receipt| {
+                (count + 1, sats + receipt.sats)
+            })
+    }
+
+    pub fn insert(&self, event: &Event) {
+        if event.kind == Kind::ZapReceipt {
+            if let Some(receipt) = zap_receipt(event) {
+                let Some(note) = receipt.note else {
+                    return;
+                };
+                self.zaps.update(|map| {
+                    map.entry(note).or_default().insert(event.id, receipt);
+                });
+            }
+            return;
+        }
+        if event.kind != Kind::Reaction {
+            return;
+        }
+        let Some(note) = reacted_note(event) else {
+            return;
+        };
+        let reaction = (event.pubkey, reaction_key(event));
+        let repeated = self.reactions.with_untracked(|map| {
+            map.get(&note)
+                .is_some_and(|reactions| reactions.values().any(|r| *r == reaction))
+        });
+        if repeated {
+            return;
+        }
+        self.reactions.update(|map| {
+            map.entry(note).or_default().insert(event.id, reaction);
+        });
+    }
+
+    fn request(&self, note: EventId) {
+        let is_new = self
+            .requested
+            .try_update_value(|set| set.insert(note))
+            .unwrap_or(false);
+        if !is_new {
+            return;
+        }
+        let first_in_batch = self
+            .queue
+            .try_update_value(|queue| {
+                queue.push(note);
+                queue.len() == 1
+            })
+            .unwrap_or(false);
+        if first_in_batch {
+            let store = *self;
+            set_timeout(move || store.flush(), BATCH_DELAY);
+        }
+    }
+
+    fn flush(&self) {
+        let batch = self
+            .queue
+            .try_update_value(std::mem::take)
+            .unwrap_or_default();
+        if batch.is_empty() {
+            return;
+        }
+        let Some((live, notes)) = self.live.try_update_value(|(id, notes)| {
+            notes.extend(batch.iter().copied());
+            while notes.len() > LIVE_NOTES {
+                notes.pop_front();
+            }
+            (id.clone(), notes.iter().copied().collect::<Vec<_>>())
+        }) else {
+            return;
+        };
+        // the backlog id is known before any event for it can arrive
+        let backlog = SubscriptionId::generate();
+        self.subscriptions.update_value(|subs| {
+            subs.insert(backlog.clone());
+        });
+        let store = *self;
+        let client = self.client.get_untracked();
+        let kinds = [Kind::Reaction, Kind::ZapReceipt];
+        spawn_local(async move {
+            let stored = Filter::new().kinds(kinds).events(batch);
+            let close = SubscribeAutoCloseOptions::default().timeout(Some(BACKLOG_TIMEOUT));
+            client
+                .subscribe_with_id(backlog.clone(), vec![stored], Some(close))
+                .await;
+            let fresh = Filter::new()
+                .kinds(kinds)
+                .events(notes)
+                .since(Timestamp::now());
+            client.subscribe_with_id(live, vec![fresh], None).await;
+            // closed on EOSE or timeout, late events for it are not ours anymore
+            set_timeout(
+                move || {
+                    let _ = store
+                        .subscriptions
+                        .try_update_value(|subs| subs.remove(&backlog));
+                },
+                BACKLOG_TIMEOUT,
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reaction(content: &str, tags: Vec<Vec<&str>>) -> Event {
+        let keys = Keys::generate();
+        let tags = tags.iter().map(|t| Tag::parse(t).unwrap());
+        EventBuilder::new(Kind::Reaction, content, tags)
+            .to_event(&keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn like_dislike_and_emoji() {
+        assert_eq!(reaction_key(&reaction("+", vec![])), ReactionKey::Like);
+        assert_eq!(reaction_key(&reaction("", vec![])), ReactionKey::Like);
+        assert_eq!(reaction_key(&reaction("-", vec![])), ReactionKey::Dislike);
+        assert_eq!(
+            reaction_key(&reaction("🤙", vec![])),
+            ReactionKey::Emoji("🤙".to_string())
+        );
+    }
+
+    #[test]
+    fn custom_emoji_needs_its_tag() {
+        let tagged = reaction(
+            ":siamstr:",
+            vec![vec!["emoji", "siamstr", "https://siamstr.com/emoji.png"]],
+        );
+        assert_eq!(
+            reaction_key(&tagged),
+            ReactionKey::Custom {
+                shortcode: "siamstr".to_string(),
+                url: "https://siamstr.com/emoji.png".to_string()
+            }
+        );
+        assert_eq!(
+            reaction_key(&reaction(":siamstr:", vec![])),
+            ReactionKey::Emoji(":siamstr:".to_string())
+        );
+    }
+
+    #[test]
+    fn reacted_note_is_last_e_tag() {
+        let root = EventId::all_zeros().to_hex();
+        let note = EventId::from_slice(&[1; 32]).unwrap();
+        let event = reaction("+", vec![vec!["e", &root], vec!["e", &note.to_hex()]]);
+        assert_eq!(reacted_note(&event), Some(note));
+        assert_eq!(reacted_note(&reaction("+", vec![])), None);
+    }
+
+    #[test]
+    fn summary_is_sorted_by_count() {
+        let reactions: Vec<(PublicKey, ReactionKey)> = [
+            ReactionKey::Emoji("🤙".to_string()),
+            ReactionKey::Like,
+            ReactionKey::Emoji("🤙".to_string()),
+            ReactionKey::Dislike,
+            ReactionKey::Like,
+            ReactionKey::Emoji("🤙".to_string()),
+        ]
+        .into_iter()
+        .map(|key| (Keys::generate().public_key(), key))
+        .collect();
+        assert_eq!(
+            summarize(reactions.iter()),
+            vec![
+                (ReactionKey::Emoji("🤙".to_string()), 3),
+                (ReactionKey::Like, 2),
+                (ReactionKey::Dislike, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_reactions_count_once() {
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+        let reactions = [
+            (alice, ReactionKey::Like),
+            (alice, ReactionKey::Like),
+            (alice, ReactionKey::Emoji("🤙".to_string())),
+            (bob, ReactionKey::Like),
+        ];
+        assert_eq!(
+            summarize(reactions.iter()),
+            vec![
+                (ReactionKey::Like, 2),
+                (ReactionKey::Emoji("🤙".to_string()), 1),
+            ]
+        );
+    }
+}
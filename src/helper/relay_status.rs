@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::nostr::types::time::Instant;
+use nostr_sdk::prelude::*;
+
+/// NOTICE and CLOSED lines kept per relay.
+const MAX_MESSAGES: usize = 20;
+
+/// What one relay has been up to since the app started.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RelayHealth {
+    pub status: Option<RelayStatus>,
+    pub attempts: usize,
+    pub successes: usize,
+    pub last_error: Option<String>,
+    pub latency: Option<Duration>,
+    pub events: usize,
+    /// `NOTICE`/`CLOSED` text, newest first
+    pub messages: VecDeque<String>,
+}
+
+impl RelayHealth {
+    fn push_message(&mut self, line: String) {
+        self.messages.push_front(line);
+        self.messages.truncate(MAX_MESSAGES);
+    }
+
+    pub fn apply(&mut self, message: &RelayMessage) {
+        match message {
+            RelayMessage::Event { .. } => self.events += 1,
+            RelayMessage::Notice { message } => self.push_message(format!("NOTICE {}", message)),
+            RelayMessage::Closed {
+                subscription_id,
+                message,
+            } => self.push_message(format!("CLOSED {} {}", subscription_id, message)),
+            RelayMessage::Ok {
+                status: false,
+                message,
+                ..
+            } => self.last_error = Some(message.clone()),
+            _ => (),
+        }
+    }
+
+    pub fn set_status(&mut self, status: RelayStatus) {
+        if status == RelayStatus::Disconnected && self.status == Some(RelayStatus::Connected) {
+            self.last_error = Some("connection lost".to_string());
+        }
+        self.status = Some(status);
+    }
+}
+
+/// Relay health shared through context, fed by pool notifications plus a poll of
+/// connection stats and an EOSE round-trip probe while the dashboard is open.
+#[derive(Clone, Copy)]
+pub struct RelayMonitor {
+    health: RwSignal<BTreeMap<Url, RelayHealth>>,
+    probes: StoredValue<HashMap<SubscriptionId, (Url, Instant)>>,
+    client: RwSignal<Client>,
+}
+
+impl RelayMonitor {
+    pub fn new(client: RwSignal<Client>) -> Self {
+        let monitor = Self {
+            health: create_rw_signal(BTreeMap::new()),
+            probes: store_value(HashMap::new()),
+            client,
+        };
+        let notify_client = client.get_untracked();
+        spawn_local(async move {
+            let mut notifications = notify_client.notifications();
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Message { relay_url, message } => {
+                        if let RelayMessage::EndOfStoredEvents(id) = &message {
+                            monitor.finish_probe(id);
+                        }
+                        monitor.update(&relay_url, |health| health.apply(&message));
+                    }
+                    RelayPoolNotification::RelayStatus { relay_url, status } => {
+                        monitor.update(&relay_url, |health| health.set_status(status));
+                    }
+                    _ => (),
+                }
+            }
+        });
+        monitor
+    }
+
+    fn update(&self, url: &Url, f: impl FnOnce(&mut RelayHealth)) {
+        self.health
+            .update(|map| f(map.entry(url.clone()).or_default()));
+    }
+
+    pub fn record_error(&self, url: &Url, error: String) {
+        self.update(url, |health| health.last_error = Some(error));
+    }
+
+    /// Tracked list of every relay currently in the pool.
+    pub fn urls(&self) -> Vec<Url> {
+        self.health.with(|map| map.keys().cloned().collect())
+    }
+
+    /// Tracked state of one relay.
+    pub fn health(&self, url: &Url) -> RelayHealth {
+        self.health
+            .with(|map| map.get(url).cloned().unwrap_or_default())
+    }
+
+    /// Tracked count of connected relays.
+    pub fn connected(&self) -> usize {
+        self.health.with(|map| {
+            map.values()
+                .filter(|health| health.status == Some(RelayStatus::Connected))
+                .count()
+        })
+    }
+
+    /// Pull status and connection counters from the pool, then time a probe
+    /// `REQ` to each connected relay.
+    pub fn refresh(&self) {
+        let monitor = *self;
+        let client = self.client.get_untracked();
+        spawn_local(async move {
+            let relays = client.relays().await;
+            let _ = monitor.health.try_update(|map| {
+                map.retain(|url, _| relays.contains_key(url));
+            });
+            for (url, relay) in relays {
+                let status = relay.status().await;
+                let stats = relay.stats();
+                monitor.update(&url, |health| {
+                    health.set_status(status);
+                    health.attempts = stats.attempts();
+                    health.successes = stats.success();
+                });
+                if status == RelayStatus::Connected {
+                    monitor.probe(&client, url).await;
+                }
+            }
+        });
+    }
+
+    async fn probe(&self, client: &Client, url: Url) {
+        let id = SubscriptionId::generate();
+        self.probes.update_value(|probes| {
+            probes.insert(id.clone(), (url.clone(), Instant::now()));
+        });
+        // nothing has the all-zero id, the relay answers with a bare EOSE
+        let filter = Filter::new().id(EventId::all_zeros()).limit(1);
+        let opts = SubscribeAutoCloseOptions::default().filter(FilterOptions::ExitOnEOSE);
+        if let Err(e) = client
+            .subscribe_with_id_to([url.clone()], id.clone(), vec![filter], Some(opts))
+            .await
+        {
+            log!("probe {} failed: {}", url, e);
+            self.probes.update_value(|probes| {
+                probes.remove(&id);
+            });
+        }
+    }
+
+    fn finish_probe(&self, id: &SubscriptionId) {
+        let Some((url, sent)) = self
+            .probes
+            .try_update_value(|probes| probes.remove(id))
+            .flatten()
+        else {
+            return;
+        };
+        let latency = sent.elapsed();
+        self.update(&url, |health| health.latency = Some(latency));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_events_and_keeps_recent_messages() {
+        let mut health = RelayHealth::default();
+        let event = EventBuilder::text_note("gm", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        health.apply(&RelayMessage::event(SubscriptionId::new("feed"), event));
+        health.apply(&RelayMessage::notice("slow down"));
+        health.apply(&RelayMessage::closed(
+            SubscriptionId::new("feed"),
+            "rate-limited: too many subs",
+        ));
+        assert_eq!(health.events, 1);
+        assert_eq!(
+            health.messages,
+            vec![
+                "CLOSED feed rate-limited: too many subs".to_string(),
+                "NOTICE slow down".to_string(),
+            ]
+        );
+        for n in 0..MAX_MESSAGES {
+            health.apply(&RelayMessage::notice(n.to_string()));
+        }
+        assert_eq!(health.messages.len(), MAX_MESSAGES);
+    }
+
+    #[test]
+    fn rejected_publish_and_dropped_connection_are_errors() {
+        let mut health = RelayHealth::default();
+        health.apply(&RelayMessage::ok(
+            EventId::all_zeros(),
+            false,
+            "blocked: not on whitelist",
+        ));
+        assert_eq!(
+            health.last_error.as_deref(),
+            Some("blocked: not on whitelist")
+        );
+
+        health.set_status(RelayStatus::Connected);
+        health.set_status(RelayStatus::Disconnected);
+        assert_eq!(health.last_error.as_deref(), Some("connection lost"));
+    }
+}
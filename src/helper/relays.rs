@@ -0,0 +1,218 @@
+use leptos::logging::log;
+use leptos::window;
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const STORAGE_KEY: &str = "siamstr-feed:relays";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RelayConfig {
+    pub url: String,
+    pub read: bool,
+    pub write: bool,
+}
+
+impl RelayConfig {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            read: true,
+            write: true,
+        }
+    }
+
+    fn flags(&self) -> RelayServiceFlags {
+        let mut flags = RelayServiceFlags::PING;
+        if self.read {
+            flags.add(RelayServiceFlags::READ);
+        }
+        if self.write {
+            flags.add(RelayServiceFlags::WRITE);
+        }
+        flags
+    }
+}
+
+pub fn default_relays() -> Vec<RelayConfig> {
+    vec![
+        RelayConfig::new("wss://relay.siamstr.com"),
+        RelayConfig::new("wss://relay.notoshi.win"),
+        RelayConfig {
+            url: "wss://bostr.lecturify.net".to_string(),
+            read: true,
+            write: false,
+        },
+    ]
+}
+
+/// Normalize user input into a relay url, `None` if it is not a websocket url.
+pub fn parse_relay_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("wss://{}", input)
+    };
+    let url = Url::parse(&with_scheme).ok()?;
+    // `Url` adds a `/` to a bare host, the defaults are written without it
+    matches!(url.scheme(), "ws" | "wss").then(|| url.as_str().trim_end_matches('/').to_string())
+}
+
+/// `true` when `url` is already in `relays`, however either was written.
+pub fn has_relay(relays: &[RelayConfig], url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    relays
+        .iter()
+        .any(|relay| Url::parse(&relay.url).is_ok_and(|existing| existing == url))
+}
+
+/// Relays the user publishes to.
+pub fn write_urls(relays: &[RelayConfig]) -> Vec<Url> {
+    relays
+        .iter()
+        .filter(|relay| relay.write)
+        .filter_map(|relay| Url::parse(&relay.url).ok())
+        .collect()
+}
+
+/// Saved relay list json, [`default_relays`] when missing, broken or empty.
+fn relays_from_json(json: Option<&str>) -> Vec<RelayConfig> {
+    json.and_then(|json| serde_json::from_str::<Vec<RelayConfig>>(json).ok())
+        .filter(|relays| !relays.is_empty())
+        .unwrap_or_else(default_relays)
+}
+
+/// Relay list saved in localStorage, falling back to [`default_relays`].
+pub fn load_relays() -> Vec<RelayConfig> {
+    let json = window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten());
+    relays_from_json(json.as_deref())
+}
+
+pub fn save_relays(relays: &[RelayConfig]) {
+    if let (Some(storage), Ok(json)) = (
+        window().local_storage().ok().flatten(),
+        serde_json::to_string(relays),
+    ) {
+        let _ = storage.set_item(STORAGE_KEY, &json);
+    }
+}
+
+/// Bring the client's relay pool in line with `relays` and connect whatever is new.
+/// Returns the relays that could not be added or removed, with the reason.
+pub async fn apply_relays(client: &Client, relays: &[RelayConfig]) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    for (url, relay) in client.relays().await {
+        match relays
+            .iter()
+            .find(|r| Url::parse(&r.url).ok().as_ref() == Some(&url))
+        {
+            Some(config) => {
+                // `remove` xors in nostr-relay-pool, only touch flags that differ
+                let flags = relay.flags();
+                for (flag, wanted) in [
+                    (RelayServiceFlags::READ, config.read),
+                    (RelayServiceFlags::WRITE, config.write),
+                ] {
+                    match (flags.has(flag), wanted) {
+                        (false, true) => flags.add(flag),
+                        (true, false) => flags.remove(flag),
+                        _ => (),
+                    }
+                }
+            }
+            None => {
+                if let Err(e) = client.remove_relay(url.as_str()).await {
+                    log!("remove relay {}: {}", url, e);
+                    errors.push((url.to_string(), e.to_string()));
+                }
+            }
+        }
+    }
+    for config in relays {
+        let opts = RelayOptions::new().flags(config.flags());
+        if let Err(e) = client.add_relay_with_opts(config.url.as_str(), opts).await {
+            log!("add relay {}: {}", config.url, e);
+            errors.push((config.url.clone(), e.to_string()));
+        }
+    }
+    client.connect().await;
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relay_urls_are_normalized() {
+        assert_eq!(
+            parse_relay_url(" relay.siamstr.com ").as_deref(),
+            Some("wss://relay.siamstr.com")
+        );
+        assert_eq!(
+            parse_relay_url("ws://localhost:7777/").as_deref(),
+            Some("ws://localhost:7777")
+        );
+        assert_eq!(
+            parse_relay_url("wss://relay.example/nostr").as_deref(),
+            Some("wss://relay.example/nostr")
+        );
+        assert_eq!(parse_relay_url("https://relay.example"), None);
+        assert_eq!(parse_relay_url("not a url"), None);
+    }
+
+    #[test]
+    fn the_same_relay_is_found_either_way() {
+        let relays = default_relays();
+        let url = parse_relay_url("relay.siamstr.com").unwrap();
+        assert!(has_relay(&relays, &url));
+        assert!(has_relay(&relays, "wss://relay.siamstr.com/"));
+        assert!(!has_relay(&relays, "wss://relay.example"));
+    }
+
+    #[test]
+    fn read_only_relays_are_not_written_to() {
+        let urls = write_urls(&default_relays());
+        assert_eq!(urls.len(), 2);
+        assert!(!urls.contains(&Url::parse("wss://bostr.lecturify.net").unwrap()));
+    }
+
+    #[test]
+    fn saved_lists_fall_back_to_defaults() {
+        assert_eq!(relays_from_json(None), default_relays());
+        assert_eq!(relays_from_json(Some("[]")), default_relays());
+        assert_eq!(relays_from_json(Some("{broken")), default_relays());
+        let saved = vec![RelayConfig::new("wss://relay.example")];
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(relays_from_json(Some(&json)), saved);
+    }
+
+    #[tokio::test]
+    async fn pool_follows_the_config() {
+        let client = Client::default();
+        client.add_relay("ws://127.0.0.1:1").await.unwrap();
+        client.add_relay("ws://127.0.0.1:2").await.unwrap();
+        let config = vec![
+            RelayConfig {
+                url: "ws://127.0.0.1:1".to_string(),
+                read: true,
+                write: false,
+            },
+            RelayConfig::new("ws://127.0.0.1:3"),
+        ];
+        assert!(apply_relays(&client, &config).await.is_empty());
+        let relays = client.relays().await;
+        let mut urls: Vec<String> = relays.keys().map(|url| url.to_string()).collect();
+        urls.sort();
+        assert_eq!(urls, vec!["ws://127.0.0.1:1/", "ws://127.0.0.1:3/"]);
+        let flags = relays[&Url::parse("ws://127.0.0.1:1").unwrap()].flags();
+        assert!(flags.has(RelayServiceFlags::READ));
+        assert!(!flags.has(RelayServiceFlags::WRITE));
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use leptos::logging::log;
+use nostr_sdk::nostr::nips::nip11::RelayInformationDocument;
+use nostr_sdk::prelude::*;
+
+/// Well known NIP-50 relays, tried next to the user's own when searching.
+pub const SEARCH_RELAYS: [&str; 2] = ["wss://relay.nostr.band", "wss://search.nos.today"];
+/// Results asked of each relay.
+const RESULT_LIMIT: usize = 50;
+const NIP_SEARCH: u16 = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchMode {
+    Notes,
+    Profiles,
+}
+
+impl SearchMode {
+    /// `?mode=` value, anything unknown searches notes.
+    pub fn from_param(param: &str) -> Self {
+        match param {
+            "profiles" => SearchMode::Profiles,
+            _ => SearchMode::Notes,
+        }
+    }
+
+    pub fn param(&self) -> &'static str {
+        match self {
+            SearchMode::Notes => "notes",
+            SearchMode::Profiles => "profiles",
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            SearchMode::Notes => Kind::TextNote,
+            SearchMode::Profiles => Kind::Metadata,
+        }
+    }
+}
+
+/// `/search` link for a query.
+pub fn search_path(query: &str, mode: SearchMode) -> String {
+    let mut url = Url::parse("https://siamstr.local/search").expect("static url");
+    url.query_pairs_mut()
+        .append_pair("q", query.trim())
+        .append_pair("mode", mode.param());
+    format!("{}?{}", url.path(), url.query().unwrap_or_default())
+}
+
+pub fn search_filter(query: &str, mode: SearchMode) -> Filter {
+    Filter::new()
+        .kind(mode.kind())
+        .search(query.trim())
+        .limit(RESULT_LIMIT)
+}
+
+pub fn supports_search(document: &RelayInformationDocument) -> bool {
+    document
+        .supported_nips
+        .as_ref()
+        .is_some_and(|nips| nips.contains(&NIP_SEARCH))
+}
+
+/// Lowercased words of a query, a local match needs all of them.
+pub fn search_terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+fn contains_terms(text: &str, terms: &[String]) -> bool {
+    let text = text.to_lowercase();
+    !terms.is_empty() && terms.iter().all(|term| text.contains(term.as_str()))
+}
+
+pub fn note_matches(event: &Event, terms: &[String]) -> bool {
+    event.kind == Kind::TextNote && contains_terms(event.content(), terms)
+}
+
+pub fn profile_matches(metadata: &Metadata, terms: &[String]) -> bool {
+    let text = [
+        &metadata.name,
+        &metadata.display_name,
+        &metadata.nip05,
+        &metadata.about,
+    ]
+    .into_iter()
+    .flatten()
+    .map(String::as_str)
+    .collect::<Vec<_>>()
+    .join(" ");
+    contains_terms(&text, terms)
+}
+
+/// Notes from every relay once each, newest first.
+pub fn merge_notes(events: impl IntoIterator<Item = Event>) -> Vec<Event> {
+    let mut seen = HashSet::new();
+    let mut notes: Vec<Event> = events
+        .into_iter()
+        .filter(|event| event.kind == Kind::TextNote && seen.insert(event.id))
+        .collect();
+    notes.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+    notes
+}
+
+/// Authors of the kind-0s from every relay, each once, in the order found.
+pub fn merge_profiles(events: &[Event]) -> Vec<PublicKey> {
+    let mut seen = HashSet::new();
+    events
+        .iter()
+        .filter(|event| event.kind == Kind::Metadata)
+        .map(|event| event.pubkey)
+        .filter(|public_key| seen.insert(*public_key))
+        .collect()
+}
+
+/// The pool's NIP-11 document when it has one, else a fresh fetch.
+async fn advertises_search(client: &Client, url: &Url) -> bool {
+    if let Ok(relay) = client.relay(url).await {
+        let document = relay.document().await;
+        if document.supported_nips.is_some() {
+            return supports_search(&document);
+        }
+    }
+    match RelayInformationDocument::get(url.clone(), None).await {
+        Ok(document) => supports_search(&document),
+        Err(e) => {
+            log!("relay info {}: {}", url, e);
+            false
+        }
+    }
+}
+
+/// Pool relays and `candidates` whose NIP-11 document lists NIP-50.
+pub async fn search_relays(client: &Client, candidates: &[Url]) -> Vec<Url> {
+    let mut urls: Vec<Url> = client.relays().await.into_keys().collect();
+    for url in candidates {
+        if !urls.contains(url) {
+            urls.push(url.clone());
+        }
+    }
+    let mut found = Vec::new();
+    for url in urls {
+        if advertises_search(client, &url).await {
+            found.push(url);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(keys: &Keys, content: &str, at: u64) -> Event {
+        EventBuilder::text_note(content, [])
+            .custom_created_at(Timestamp::from(at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn filter_and_nip11() {
+        let filter = search_filter(" bitcoin ", SearchMode::Profiles);
+        assert_eq!(filter.search.as_deref(), Some("bitcoin"));
+        assert!(filter.kinds.unwrap().contains(&Kind::Metadata));
+
+        let mut document = RelayInformationDocument::new();
+        assert!(!supports_search(&document));
+        document.supported_nips = Some(vec![1, 11, 50]);
+        assert!(supports_search(&document));
+
+        assert_eq!(SearchMode::from_param("profiles"), SearchMode::Profiles);
+        assert_eq!(SearchMode::from_param("x"), SearchMode::Notes);
+        assert_eq!(
+            search_path(" siamstr & nostr ", SearchMode::Notes),
+            "/search?q=siamstr+%26+nostr&mode=notes"
+        );
+    }
+
+    #[test]
+    fn local_matching_needs_every_term() {
+        let keys = Keys::generate();
+        let terms = search_terms("Nostr  สยาม");
+        assert!(note_matches(&note(&keys, "สวัสดี nostr สยาม", 1), &terms));
+        assert!(!note_matches(&note(&keys, "nostr only", 1), &terms));
+        assert!(!note_matches(&note(&keys, "anything", 1), &[]));
+
+        let metadata = Metadata::new().name("vaz").about("Building NOSTR in สยาม");
+        assert!(profile_matches(&metadata, &terms));
+        assert!(profile_matches(&metadata, &search_terms("VAZ")));
+        assert!(!profile_matches(&metadata, &search_terms("alice")));
+    }
+
+    #[test]
+    fn merges_relay_results() {
+        let keys = Keys::generate();
+        let old = note(&keys, "old", 10);
+        let new = note(&keys, "new", 20);
+        let merged = merge_notes([old.clone(), new.clone(), old.clone()]);
+        assert_eq!(merged, vec![new, old]);
+
+        let other = Keys::generate();
+        let profile = |keys: &Keys| {
+            EventBuilder::metadata(&Metadata::new().name("x"))
+                .to_event(keys)
+                .unwrap()
+        };
+        assert_eq!(
+            merge_profiles(&[profile(&other), profile(&keys), profile(&other)]),
+            vec![other.public_key(), keys.public_key()]
+        );
+    }
+}
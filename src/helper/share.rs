@@ -0,0 +1,121 @@
+use nostr_sdk::prelude::*;
+
+use crate::helper::outbox::{RelayList, MAX_HINTS};
+
+/// Three ways to hand over a note or profile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareLinks {
+    /// `nevent` or `nprofile`
+    pub entity: String,
+    /// `nostr:` URI for other clients
+    pub uri: String,
+    /// This site's page for it
+    pub web: String,
+}
+
+impl ShareLinks {
+    fn new(entity: String, origin: &str) -> Self {
+        Self {
+            uri: format!("nostr:{}", entity),
+            web: format!("{}/nostr/{}", origin.trim_end_matches('/'), entity),
+            entity,
+        }
+    }
+}
+
+/// Relays to embed: where the author publishes, else the user's own relays.
+pub fn share_hints(author_relays: Option<RelayList>, own: &[String]) -> Vec<String> {
+    let write: Vec<String> = author_relays
+        .map(|list| {
+            list.write
+                .iter()
+                .map(|url| url.as_str().trim_end_matches('/').to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let hints = if write.is_empty() {
+        own.to_vec()
+    } else {
+        write
+    };
+    hints.into_iter().take(MAX_HINTS).collect()
+}
+
+pub fn share_note(event: &Event, hints: Vec<String>, origin: &str) -> ShareLinks {
+    let entity = Nip19Event::new(event.id, hints)
+        .author(event.pubkey)
+        .to_bech32()
+        .unwrap_or_default();
+    ShareLinks::new(entity, origin)
+}
+
+pub fn share_profile(public_key: PublicKey, hints: Vec<String>, origin: &str) -> ShareLinks {
+    let relays: Vec<Url> = hints
+        .iter()
+        .filter_map(|hint| Url::parse(hint).ok())
+        .collect();
+    let entity = Nip19Profile::new(public_key, relays)
+        .and_then(|profile| profile.to_bech32())
+        .unwrap_or_default();
+    ShareLinks::new(entity, origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn author_relays_come_first() {
+        let own = vec!["wss://own.example".to_string()];
+        assert_eq!(share_hints(None, &own), own);
+        let list = RelayList {
+            read: vec![Url::parse("wss://inbox.example").unwrap()],
+            write: [
+                "wss://a.example",
+                "wss://b.example",
+                "wss://c.example",
+                "wss://d.example",
+            ]
+            .iter()
+            .map(|url| Url::parse(url).unwrap())
+            .collect(),
+        };
+        assert_eq!(
+            share_hints(Some(list), &own),
+            vec!["wss://a.example", "wss://b.example", "wss://c.example"]
+        );
+    }
+
+    #[test]
+    fn links_round_trip() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("hi", []).to_event(&keys).unwrap();
+        let links = share_note(
+            &note,
+            vec!["wss://relay.example".to_string()],
+            "https://feed.siamstr.com/",
+        );
+        assert_eq!(links.uri, format!("nostr:{}", links.entity));
+        assert_eq!(
+            links.web,
+            format!("https://feed.siamstr.com/nostr/{}", links.entity)
+        );
+        let Ok(Nip19::Event(event)) = Nip19::from_bech32(&links.entity) else {
+            panic!("not an nevent");
+        };
+        assert_eq!(event.event_id, note.id);
+        assert_eq!(event.author, Some(keys.public_key()));
+        assert_eq!(event.relays, vec!["wss://relay.example"]);
+
+        let links = share_profile(
+            keys.public_key(),
+            vec!["wss://relay.example".to_string()],
+            "",
+        );
+        let Ok(Nip19::Profile(profile)) = Nip19::from_bech32(&links.entity) else {
+            panic!("not an nprofile");
+        };
+        assert_eq!(profile.public_key, keys.public_key());
+        assert_eq!(profile.relays.len(), 1);
+    }
+}
@@ -0,0 +1,79 @@
+use std::str::FromStr;
+
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+const NSEC_ANON: &str = "nsec1nuq7e2w37t89apaupmxj5ylg027mnfmtzv4d2rcmjmt7fwjhhlmsxdmlq7";
+
+/// How the current visitor is identified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoginMode {
+    /// Read-only, nothing can be signed yet
+    LoggedOut,
+    /// Shared anonymous key ("ปลาอานนท์")
+    Guest,
+    /// NIP-07 browser extension (window.nostr)
+    Extension,
+}
+
+impl LoginMode {
+    pub fn can_sign(&self) -> bool {
+        !matches!(self, LoginMode::LoggedOut)
+    }
+}
+
+pub fn guest_keys() -> Keys {
+    Keys::from_str(NSEC_ANON).expect("ปลาอานนท์")
+}
+
+/// `true` when a NIP-07 signer is injected into the page.
+pub fn has_extension() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Nip07Signer::is_available()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        false
+    }
+}
+
+/// Sign with the shared anonymous key.
+pub async fn use_guest(client: &Client) -> Keys {
+    let keys = guest_keys();
+    client.set_signer(Some(keys.clone().into())).await;
+    keys
+}
+
+/// Ask the extension for its public key and route all signing through it.
+pub async fn use_extension(client: &Client) -> Result<Keys, String> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let signer = NostrSigner::nip07().map_err(|e| e.to_string())?;
+        let public_key = signer.public_key().await.map_err(|e| e.to_string())?;
+        client.set_signer(Some(signer)).await;
+        Ok(Keys::from_public_key(public_key))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = client;
+        Err("NIP-07 is only available in the browser".to_string())
+    }
+}
+
+pub async fn logout(client: &Client) -> Keys {
+    client.set_signer(None).await;
+    guest_keys()
+}
+
+/// Hex pubkey of the visitor, `None` while logged out so no note is drawn as "me".
+pub fn use_self_pubkey() -> Memo<Option<String>> {
+    let app_keys = use_context::<RwSignal<Keys>>().expect("app key init");
+    let login_mode = use_context::<RwSignal<LoginMode>>().expect("login mode init");
+    create_memo(move |_| {
+        login_mode
+            .get()
+            .can_sign()
+            .then(|| app_keys.with(|keys| keys.public_key().to_hex()))
+    })
+}
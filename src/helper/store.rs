@@ -0,0 +1,407 @@
+use std::collections::{BTreeSet, HashMap};
+
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+/// Tag values the store is indexed by.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IndexedTag {
+    Event(EventId),
+    PublicKey(PublicKey),
+    /// Lowercased hashtag
+    Hashtag(String),
+}
+
+/// `(kind, author, d)` of a replaceable event, `d` is `None` for plain replaceables.
+type Address = (Kind, PublicKey, Option<String>);
+/// Event ids ordered oldest first.
+type Timeline = BTreeSet<(Timestamp, EventId)>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Inserted {
+    New,
+    /// Took the place of an older version of the same replaceable event
+    Replaced(EventId),
+    Duplicate,
+    /// A newer version of this replaceable event is already stored
+    Outdated,
+}
+
+fn indexed_tags(event: &Event) -> Vec<IndexedTag> {
+    event
+        .iter_tags()
+        .filter_map(|tag| match tag.as_vec() {
+            [kind, value, ..] if kind == "e" => {
+                EventId::from_hex(value).ok().map(IndexedTag::Event)
+            }
+            [kind, value, ..] if kind == "p" => {
+                PublicKey::from_hex(value).ok().map(IndexedTag::PublicKey)
+            }
+            [kind, value, ..] if kind == "t" => Some(IndexedTag::Hashtag(value.to_lowercase())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn address(event: &Event) -> Option<Address> {
+    if event.kind.is_replaceable() {
+        Some((event.kind, event.pubkey, None))
+    } else if event.kind.is_parameterized_replaceable() {
+        let d = event.identifier().unwrap_or_default().to_string();
+        Some((event.kind, event.pubkey, Some(d)))
+    } else {
+        None
+    }
+}
+
+/// Events by id plus the secondary indexes, no reactivity.
+#[derive(Debug, Default)]
+pub struct EventIndex {
+    events: HashMap<EventId, Event>,
+    by_time: Timeline,
+    by_kind: HashMap<Kind, Timeline>,
+    by_author: HashMap<PublicKey, Timeline>,
+    by_tag: HashMap<IndexedTag, Timeline>,
+    replaceable: HashMap<Address, EventId>,
+}
+
+impl EventIndex {
+    pub fn insert(&mut self, event: Event) -> Inserted {
+        if self.events.contains_key(&event.id) {
+            return Inserted::Duplicate;
+        }
+        let mut replaced = None;
+        if let Some(address) = address(&event) {
+            if let Some(current) = self
+                .replaceable
+                .get(&address)
+                .and_then(|id| self.events.get(id))
+            {
+                // NIP-01: newest wins, the lowest id breaks a tie
+                if (current.created_at, event.id) < (event.created_at, current.id) {
+                    replaced = Some(current.id);
+                } else {
+                    return Inserted::Outdated;
+                }
+            }
+            self.replaceable.insert(address, event.id);
+        }
+        if let Some(old) = replaced {
+            self.remove(&old);
+        }
+        let entry = (event.created_at, event.id);
+        self.by_time.insert(entry);
+        self.by_kind.entry(event.kind).or_default().insert(entry);
+        self.by_author
+            .entry(event.pubkey)
+            .or_default()
+            .insert(entry);
+        for tag in indexed_tags(&event) {
+            self.by_tag.entry(tag).or_default().insert(entry);
+        }
+        self.events.insert(event.id, event);
+        match replaced {
+            Some(old) => Inserted::Replaced(old),
+            None => Inserted::New,
+        }
+    }
+
+    fn remove(&mut self, id: &EventId) {
+        let Some(event) = self.events.remove(id) else {
+            return;
+        };
+        let entry = (event.created_at, event.id);
+        self.by_time.remove(&entry);
+        if let Some(set) = self.by_kind.get_mut(&event.kind) {
+            set.remove(&entry);
+        }
+        if let Some(set) = self.by_author.get_mut(&event.pubkey) {
+            set.remove(&entry);
+        }
+        for tag in indexed_tags(&event) {
+            if let Some(set) = self.by_tag.get_mut(&tag) {
+                set.remove(&entry);
+            }
+        }
+    }
+
+    pub fn get(&self, id: &EventId) -> Option<&Event> {
+        self.events.get(id)
+    }
+
+    fn collect<'a>(&'a self, timeline: Option<&'a Timeline>) -> Vec<&'a Event> {
+        timeline
+            .into_iter()
+            .flatten()
+            .filter_map(|(_, id)| self.events.get(id))
+            .collect()
+    }
+
+    /// Everything, oldest first.
+    pub fn all(&self) -> Vec<&Event> {
+        self.collect(Some(&self.by_time))
+    }
+
+    #[allow(dead_code)]
+    pub fn by_kind(&self, kind: Kind) -> Vec<&Event> {
+        self.collect(self.by_kind.get(&kind))
+    }
+
+    pub fn by_author(&self, author: &PublicKey) -> Vec<&Event> {
+        self.collect(self.by_author.get(author))
+    }
+
+    pub fn tagged(&self, tag: &IndexedTag) -> Vec<&Event> {
+        self.collect(self.by_tag.get(tag))
+    }
+
+    /// Current version of a replaceable (`identifier: None`) or parameterized
+    /// replaceable event.
+    pub fn replaceable(
+        &self,
+        kind: Kind,
+        author: PublicKey,
+        identifier: Option<&str>,
+    ) -> Option<&Event> {
+        let address = (kind, author, identifier.map(str::to_string));
+        self.replaceable
+            .get(&address)
+            .and_then(|id| self.events.get(id))
+    }
+}
+
+/// What a view read from the store, changes only wake the views that read it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum StoreKey {
+    All,
+    Id(EventId),
+    Kind(Kind),
+    Author(PublicKey),
+    Tag(IndexedTag),
+}
+
+/// App-wide event store. Lookups are tracked per id, kind, author or tag, so an
+/// insert only re-runs the views that queried something it touches.
+#[derive(Clone, Copy)]
+pub struct EventStore {
+    index: StoredValue<EventIndex>,
+    triggers: StoredValue<HashMap<StoreKey, Trigger>>,
+    owner: Owner,
+}
+
+impl Default for EventStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventStore {
+    pub fn new() -> Self {
+        Self {
+            index: store_value(EventIndex::default()),
+            triggers: store_value(HashMap::new()),
+            owner: Owner::current().expect("event store owner"),
+        }
+    }
+
+    fn track(&self, key: StoreKey) {
+        let existing = self.triggers.with_value(|map| map.get(&key).copied());
+        let trigger = existing.unwrap_or_else(|| {
+            // owned by the app, not by whichever view asked first
+            let trigger = with_owner(self.owner, create_trigger);
+            self.triggers.update_value(|map| {
+                map.insert(key, trigger);
+            });
+            trigger
+        });
+        trigger.track();
+    }
+
+    fn notify(&self, event: &Event) {
+        let mut keys = vec![
+            StoreKey::All,
+            StoreKey::Id(event.id),
+            StoreKey::Kind(event.kind),
+            StoreKey::Author(event.pubkey),
+        ];
+        keys.extend(indexed_tags(event).into_iter().map(StoreKey::Tag));
+        self.notify_keys(&keys);
+    }
+
+    fn notify_keys(&self, keys: &[StoreKey]) {
+        let triggers: Vec<Trigger> = self.triggers.with_value(|map| {
+            keys.iter()
+                .filter_map(|key| map.get(key).copied())
+                .collect()
+        });
+        triggers.into_iter().for_each(|trigger| trigger.notify());
+    }
+
+    /// Store `event`, returns `false` for duplicates and outdated replaceables.
+    pub fn insert(&self, event: Event) -> bool {
+        let touched = event.clone();
+        match self.index.try_update_value(|index| index.insert(event)) {
+            Some(Inserted::New) => (),
+            // views holding the old version look it up again
+            Some(Inserted::Replaced(old)) => self.notify_keys(&[StoreKey::Id(old)]),
+            _ => return false,
+        }
+        self.notify(&touched);
+        true
+    }
+
+    pub fn get(&self, id: &EventId) -> Option<Event> {
+        self.track(StoreKey::Id(*id));
+        self.get_untracked(id)
+    }
+
+    pub fn get_untracked(&self, id: &EventId) -> Option<Event> {
+        self.index.with_value(|index| index.get(id).cloned())
+    }
+
+    pub fn all(&self) -> Vec<Event> {
+        self.track(StoreKey::All);
+        self.index
+            .with_value(|index| index.all().into_iter().cloned().collect())
+    }
+
+    #[allow(dead_code)]
+    pub fn by_kind(&self, kind: Kind) -> Vec<Event> {
+        self.track(StoreKey::Kind(kind));
+        self.index
+            .with_value(|index| index.by_kind(kind).into_iter().cloned().collect())
+    }
+
+    pub fn by_author(&self, author: &PublicKey) -> Vec<Event> {
+        self.track(StoreKey::Author(*author));
+        self.index
+            .with_value(|index| index.by_author(author).into_iter().cloned().collect())
+    }
+
+    pub fn tagged(&self, tag: &IndexedTag) -> Vec<Event> {
+        self.track(StoreKey::Tag(tag.clone()));
+        self.index
+            .with_value(|index| index.tagged(tag).into_iter().cloned().collect())
+    }
+
+    pub fn replaceable(
+        &self,
+        kind: Kind,
+        author: PublicKey,
+        identifier: Option<&str>,
+    ) -> Option<Event> {
+        self.track(StoreKey::Author(author));
+        self.replaceable_untracked(kind, author, identifier)
+    }
+
+    pub fn replaceable_untracked(
+        &self,
+        kind: Kind,
+        author: PublicKey,
+        identifier: Option<&str>,
+    ) -> Option<Event> {
+        self.index
+            .with_value(|index| index.replaceable(kind, author, identifier).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: &Keys, kind: Kind, created_at: u64, tags: Vec<Vec<&str>>) -> Event {
+        let tags = tags.iter().map(|t| Tag::parse(t).unwrap());
+        EventBuilder::new(kind, "", tags)
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn indexes_and_dedupes() {
+        let keys = Keys::generate();
+        let root = EventId::all_zeros();
+        let other = Keys::generate().public_key();
+        let mut index = EventIndex::default();
+        let late = event(&keys, Kind::TextNote, 20, vec![vec!["t", "Siamstr"]]);
+        let early = event(
+            &keys,
+            Kind::TextNote,
+            10,
+            vec![
+                vec!["t", "siamstr"],
+                vec!["e", &root.to_hex()],
+                vec!["p", &other.to_hex()],
+            ],
+        );
+        assert_eq!(index.insert(late.clone()), Inserted::New);
+        assert_eq!(index.insert(early.clone()), Inserted::New);
+        assert_eq!(index.insert(late.clone()), Inserted::Duplicate);
+
+        let ids = |events: Vec<&Event>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(index.all()), vec![early.id, late.id]);
+        assert_eq!(ids(index.by_kind(Kind::TextNote)), vec![early.id, late.id]);
+        assert_eq!(
+            ids(index.by_author(&keys.public_key())),
+            vec![early.id, late.id]
+        );
+        assert_eq!(
+            ids(index.tagged(&IndexedTag::Hashtag("siamstr".to_string()))),
+            vec![early.id, late.id]
+        );
+        assert_eq!(ids(index.tagged(&IndexedTag::Event(root))), vec![early.id]);
+        assert_eq!(
+            ids(index.tagged(&IndexedTag::PublicKey(other))),
+            vec![early.id]
+        );
+        assert!(index.by_kind(Kind::Reaction).is_empty());
+    }
+
+    #[test]
+    fn replaceable_keeps_newest() {
+        let keys = Keys::generate();
+        let mut index = EventIndex::default();
+        let old = event(&keys, Kind::Metadata, 10, vec![]);
+        let new = event(&keys, Kind::Metadata, 20, vec![]);
+        assert_eq!(index.insert(new.clone()), Inserted::New);
+        assert_eq!(index.insert(old.clone()), Inserted::Outdated);
+        assert_eq!(index.get(&old.id), None);
+
+        let newer = event(&keys, Kind::Metadata, 30, vec![]);
+        assert_eq!(index.insert(newer.clone()), Inserted::Replaced(new.id));
+        assert_eq!(
+            index
+                .replaceable(Kind::Metadata, keys.public_key(), None)
+                .map(|e| e.id),
+            Some(newer.id)
+        );
+        assert_eq!(index.by_author(&keys.public_key()).len(), 1);
+        assert_eq!(index.get(&new.id), None);
+    }
+
+    #[test]
+    fn parameterized_replaceable_is_per_identifier() {
+        let keys = Keys::generate();
+        let mut index = EventIndex::default();
+        let a1 = event(&keys, Kind::LongFormTextNote, 10, vec![vec!["d", "a"]]);
+        let b = event(&keys, Kind::LongFormTextNote, 10, vec![vec!["d", "b"]]);
+        let a2 = event(&keys, Kind::LongFormTextNote, 20, vec![vec!["d", "a"]]);
+        assert_eq!(index.insert(a1.clone()), Inserted::New);
+        assert_eq!(index.insert(b.clone()), Inserted::New);
+        assert_eq!(index.insert(a2.clone()), Inserted::Replaced(a1.id));
+        let pk = keys.public_key();
+        assert_eq!(
+            index
+                .replaceable(Kind::LongFormTextNote, pk, Some("a"))
+                .map(|e| e.id),
+            Some(a2.id)
+        );
+        assert_eq!(
+            index
+                .replaceable(Kind::LongFormTextNote, pk, Some("b"))
+                .map(|e| e.id),
+            Some(b.id)
+        );
+        assert_eq!(index.by_kind(Kind::LongFormTextNote).len(), 2);
+    }
+}
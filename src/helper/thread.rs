@@ -0,0 +1,201 @@
+use std::collections::{HashMap, HashSet};
+
+use nostr_sdk::prelude::*;
+
+/// NIP-10 references of a note.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThreadRefs {
+    pub root: Option<EventId>,
+    pub reply: Option<EventId>,
+    pub mentions: Vec<EventId>,
+}
+
+impl ThreadRefs {
+    /// The note this one answers, if any.
+    pub fn parent(&self) -> Option<EventId> {
+        self.reply.or(self.root)
+    }
+}
+
+/// Read `e` tags using the marked scheme, or the deprecated positional one when no tag
+/// carries a marker.
+pub fn thread_refs(event: &Event) -> ThreadRefs {
+    let e_tags: Vec<(EventId, Option<&str>)> = event
+        .iter_tags()
+        .filter_map(|tag| {
+            let values = tag.as_vec();
+            if values.first().map(String::as_str) != Some("e") {
+                return None;
+            }
+            let id = EventId::from_hex(values.get(1)?).ok()?;
+            let marker = values.get(3).map(String::as_str).filter(|m| !m.is_empty());
+            Some((id, marker))
+        })
+        .collect();
+
+    let mut refs = ThreadRefs::default();
+    if e_tags.iter().any(|(_, marker)| marker.is_some()) {
+        for (id, marker) in e_tags {
+            match marker {
+                Some("root") => refs.root = Some(id),
+                Some("reply") => refs.reply = Some(id),
+                _ => refs.mentions.push(id),
+            }
+        }
+    } else if let Some(((first, _), rest)) = e_tags.split_first() {
+        refs.root = Some(*first);
+        if let Some(((last, _), middle)) = rest.split_last() {
+            refs.reply = Some(*last);
+            refs.mentions = middle.iter().map(|(id, _)| *id).collect();
+        }
+    }
+    refs
+}
+
+/// Depth-first order of `events` (oldest reply first) with the nesting depth of each note.
+/// Notes whose parent was not loaded hang off the root, or become top level without one.
+pub fn thread_order(events: &HashMap<EventId, Event>, root: EventId) -> Vec<(EventId, usize)> {
+    let mut children: HashMap<Option<EventId>, Vec<&Event>> = HashMap::new();
+    for event in events.values() {
+        let parent = if event.id == root {
+            None
+        } else {
+            match thread_refs(event).parent() {
+                Some(parent) if events.contains_key(&parent) => Some(parent),
+                _ if events.contains_key(&root) => Some(root),
+                _ => None,
+            }
+        };
+        children.entry(parent).or_default().push(event);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|e| (e.created_at, e.id));
+    }
+
+    let mut order = Vec::with_capacity(events.len());
+    let mut seen = HashSet::new();
+    let mut stack: Vec<(EventId, usize)> = children
+        .get(&None)
+        .map(|top| top.iter().rev().map(|e| (e.id, 0)).collect())
+        .unwrap_or_default();
+    while let Some((id, depth)) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        order.push((id, depth));
+        if let Some(kids) = children.get(&Some(id)) {
+            stack.extend(kids.iter().rev().map(|e| (e.id, depth + 1)));
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(keys: &Keys, content: &str, tags: Vec<Vec<String>>, at: u64) -> Event {
+        let tags = tags.iter().map(|t| Tag::parse(t).unwrap());
+        EventBuilder::text_note(content, tags)
+            .custom_created_at(Timestamp::from(at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    fn e_tag(id: EventId, marker: &str) -> Vec<String> {
+        vec!["e".into(), id.to_hex(), String::new(), marker.into()]
+    }
+
+    fn positional(id: EventId) -> Vec<String> {
+        vec!["e".into(), id.to_hex()]
+    }
+
+    #[test]
+    fn marked_tags() {
+        let keys = Keys::generate();
+        let root = note(&keys, "root", vec![], 1);
+        let parent = note(&keys, "parent", vec![e_tag(root.id, "root")], 2);
+        let quoted = note(&keys, "quoted", vec![], 3);
+        let reply = note(
+            &keys,
+            "reply",
+            vec![
+                e_tag(quoted.id, "mention"),
+                e_tag(root.id, "root"),
+                e_tag(parent.id, "reply"),
+            ],
+            4,
+        );
+        let refs = thread_refs(&reply);
+        assert_eq!(refs.root, Some(root.id));
+        assert_eq!(refs.reply, Some(parent.id));
+        assert_eq!(refs.mentions, vec![quoted.id]);
+        assert_eq!(thread_refs(&parent).parent(), Some(root.id));
+    }
+
+    #[test]
+    fn positional_tags() {
+        let keys = Keys::generate();
+        let a = note(&keys, "a", vec![], 1);
+        let b = note(&keys, "b", vec![], 2);
+        let c = note(&keys, "c", vec![], 3);
+
+        let single = note(&keys, "1", vec![positional(a.id)], 4);
+        assert_eq!(thread_refs(&single).root, Some(a.id));
+        assert_eq!(thread_refs(&single).parent(), Some(a.id));
+
+        let many = note(
+            &keys,
+            "3",
+            vec![positional(a.id), positional(b.id), positional(c.id)],
+            5,
+        );
+        let refs = thread_refs(&many);
+        assert_eq!(refs.root, Some(a.id));
+        assert_eq!(refs.reply, Some(c.id));
+        assert_eq!(refs.mentions, vec![b.id]);
+    }
+
+    #[test]
+    fn no_tags_is_a_root() {
+        let keys = Keys::generate();
+        assert_eq!(
+            thread_refs(&note(&keys, "gm", vec![], 1)),
+            ThreadRefs::default()
+        );
+    }
+
+    #[test]
+    fn order_nests_replies() {
+        let keys = Keys::generate();
+        let root = note(&keys, "root", vec![], 1);
+        let first = note(&keys, "first", vec![e_tag(root.id, "root")], 2);
+        let nested = note(
+            &keys,
+            "nested",
+            vec![e_tag(root.id, "root"), e_tag(first.id, "reply")],
+            3,
+        );
+        let second = note(&keys, "second", vec![positional(root.id)], 4);
+        let missing_parent = note(
+            &keys,
+            "orphan",
+            vec![e_tag(root.id, "root"), e_tag(EventId::all_zeros(), "reply")],
+            5,
+        );
+        let events: HashMap<EventId, Event> = [&root, &first, &nested, &second, &missing_parent]
+            .into_iter()
+            .map(|e| (e.id, e.clone()))
+            .collect();
+        assert_eq!(
+            thread_order(&events, root.id),
+            vec![
+                (root.id, 0),
+                (first.id, 1),
+                (nested.id, 2),
+                (second.id, 1),
+                (missing_parent.id, 1),
+            ]
+        );
+    }
+}
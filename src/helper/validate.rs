@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::async_utility::futures_util::future::join_all;
+use nostr_sdk::prelude::*;
+
+/// Clock skew allowed for `created_at`, in seconds.
+pub const MAX_FUTURE_SECS: u64 = 15 * 60;
+/// Serialized size above which an event is dropped.
+pub const MAX_EVENT_BYTES: usize = 64 * 1024;
+
+/// Why an event from a relay was dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rejection {
+    TooLarge,
+    FromFuture,
+    BadId,
+    BadSignature,
+}
+
+impl Rejection {
+    pub const ALL: [Rejection; 4] = [
+        Rejection::TooLarge,
+        Rejection::FromFuture,
+        Rejection::BadId,
+        Rejection::BadSignature,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rejection::TooLarge => "ใหญ่เกินไป",
+            Rejection::FromFuture => "เวลาในอนาคต",
+            Rejection::BadId => "id ไม่ตรง",
+            Rejection::BadSignature => "ลายเซ็นผิด",
+        }
+    }
+}
+
+/// Cheap checks first, the Schnorr signature last.
+pub fn validate(event: &Event, now: Timestamp) -> Result<(), Rejection> {
+    if event.as_json().len() > MAX_EVENT_BYTES {
+        return Err(Rejection::TooLarge);
+    }
+    if event.created_at.as_u64() > now.as_u64() + MAX_FUTURE_SECS {
+        return Err(Rejection::FromFuture);
+    }
+    event.verify_id().map_err(|_| Rejection::BadId)?;
+    event.verify().map_err(|_| Rejection::BadSignature)
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RelayCounts {
+    pub accepted: usize,
+    pub rejected: BTreeMap<Rejection, usize>,
+}
+
+impl RelayCounts {
+    pub fn rejected(&self, reason: Rejection) -> usize {
+        self.rejected.get(&reason).copied().unwrap_or_default()
+    }
+}
+
+/// Gate in front of every insert, counting results per relay.
+#[derive(Clone, Copy)]
+pub struct Validator {
+    counts: RwSignal<BTreeMap<Url, RelayCounts>>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self {
+            counts: create_rw_signal(BTreeMap::new()),
+        }
+    }
+
+    /// `true` when `event` may be stored.
+    pub fn check(&self, relay: &Url, event: &Event) -> bool {
+        let result = validate(event, Timestamp::now());
+        self.counts.update(|map| {
+            let counts = map.entry(relay.clone()).or_default();
+            match result {
+                Ok(()) => counts.accepted += 1,
+                Err(reason) => *counts.rejected.entry(reason).or_default() += 1,
+            }
+        });
+        if let Err(reason) = result {
+            log!("dropped {} from {}: {:?}", event.id, relay, reason);
+        }
+        result.is_ok()
+    }
+
+    pub fn counts(&self) -> Vec<(Url, RelayCounts)> {
+        self.counts
+            .with(|map| map.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+    }
+
+    /// `get_events_of` asking each relay on its own, so every event is checked
+    /// against the relay that sent it. Reading relays of the pool, or only
+    /// `urls` when given. Fails only when no relay answered.
+    pub async fn fetch(
+        &self,
+        client: &Client,
+        urls: Option<&[Url]>,
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<Vec<Event>, String> {
+        self.fetch_all(client, urls, filters, timeout)
+            .await
+            .map(|fetched| fetched.events)
+    }
+
+    /// [`Validator::fetch`], also telling whether every relay asked sent EOSE.
+    pub async fn fetch_all(
+        &self,
+        client: &Client,
+        urls: Option<&[Url]>,
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<Fetched, String> {
+        let relays: Vec<Relay> = client
+            .relays()
+            .await
+            .into_iter()
+            .filter(|(url, relay)| {
+                urls.is_none_or(|urls| urls.contains(url)) && relay.flags().has_read()
+            })
+            .map(|(_, relay)| relay)
+            .collect();
+        if relays.is_empty() {
+            return Err("no relays to ask".to_string());
+        }
+        let answers =
+            join_all(relays.iter().map(|relay| {
+                relay.get_events_of(filters.clone(), timeout, FilterOptions::ExitOnEOSE)
+            }))
+            .await;
+        let mut seen = HashSet::new();
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+        for (relay, answer) in relays.iter().zip(answers) {
+            let url = relay.url();
+            match answer {
+                Ok(found) => events.extend(
+                    found
+                        .into_iter()
+                        .filter(|event| self.check(&url, event) && seen.insert(event.id)),
+                ),
+                Err(e) => errors.push(format!("{}: {}", url, e)),
+            }
+        }
+        if errors.len() == relays.len() {
+            return Err(errors.join(", "));
+        }
+        Ok(Fetched {
+            events,
+            complete: errors.is_empty(),
+        })
+    }
+}
+
+/// Result of [`Validator::fetch_all`].
+pub struct Fetched {
+    pub events: Vec<Event>,
+    /// Every relay asked sent EOSE, so an event missing here is missing there too
+    pub complete: bool,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(keys: &Keys, content: &str) -> Event {
+        EventBuilder::text_note(content, []).to_event(keys).unwrap()
+    }
+
+    #[test]
+    fn genuine_event_passes() {
+        let event = note(&Keys::generate(), "สวัสดี");
+        assert_eq!(validate(&event, Timestamp::now()), Ok(()));
+    }
+
+    #[test]
+    fn forged_content_breaks_the_id() {
+        let event = note(&Keys::generate(), "original");
+        let forged = event.as_json().replace("original", "forged");
+        let forged = Event::from_json(forged).unwrap();
+        assert_eq!(validate(&forged, Timestamp::now()), Err(Rejection::BadId));
+    }
+
+    #[test]
+    fn swapped_signature_is_rejected() {
+        let keys = Keys::generate();
+        let a = note(&keys, "a");
+        let b = note(&keys, "b");
+        let swapped = a
+            .as_json()
+            .replace(&a.signature().to_string(), &b.signature().to_string());
+        let swapped = Event::from_json(swapped).unwrap();
+        assert_eq!(
+            validate(&swapped, Timestamp::now()),
+            Err(Rejection::BadSignature)
+        );
+    }
+
+    #[test]
+    fn future_and_oversized_events() {
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+        let future = EventBuilder::text_note("later", [])
+            .custom_created_at(Timestamp::from(now.as_u64() + MAX_FUTURE_SECS + 60))
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(validate(&future, now), Err(Rejection::FromFuture));
+
+        let skewed = EventBuilder::text_note("soon", [])
+            .custom_created_at(Timestamp::from(now.as_u64() + 60))
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(validate(&skewed, now), Ok(()));
+
+        let huge = note(&keys, &"ก".repeat(MAX_EVENT_BYTES));
+        assert_eq!(validate(&huge, now), Err(Rejection::TooLarge));
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::{HashMap, HashSet};
+
+use leptos::logging::log;
+use leptos::*;
+use nostr_sdk::nostr::bech32;
+use nostr_sdk::prelude::*;
+use serde::Deserialize;
+
+use crate::helper::address::tag_value;
+use crate::helper::bolt11::bolt11_amount_msat;
+use crate::helper::profiles::ProfileCache;
+
+/// LNURL-pay parameters (LUD-06) with the NIP-57 extensions.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LnurlPay {
+    pub callback: String,
+    pub min_sendable: u64,
+    pub max_sendable: u64,
+    #[serde(default)]
+    pub allows_nostr: bool,
+    #[serde(default)]
+    pub nostr_pubkey: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LnurlResponse {
+    #[serde(default)]
+    pr: Option<String>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// LNURL-pay endpoint from `lud16` (`name@domain`) or the bech32 `lud06`.
+pub fn lnurl_pay_url(metadata: &Metadata) -> Option<String> {
+    if let Some((name, domain)) = metadata
+        .lud16
+        .as_deref()
+        .and_then(|lud16| lud16.trim().split_once('@'))
+    {
+        let scheme = if domain.ends_with(".onion") {
+            "http"
+        } else {
+            "https"
+        };
+        return Some(format!(
+            "{}://{}/.well-known/lnurlp/{}",
+            scheme,
+            domain,
+            name.to_lowercase()
+        ));
+    }
+    let lud06 = metadata.lud06.as_deref()?.trim().to_lowercase();
+    let (hrp, data) = bech32::decode(&lud06).ok()?;
+    if hrp.to_lowercase() != "lnurl" {
+        return None;
+    }
+    String::from_utf8(data).ok()
+}
+
+/// Bech32 `lnurl1…` form of `url`, sent along in the zap request.
+pub fn encode_lnurl(url: &str) -> Option<String> {
+    let hrp = bech32::Hrp::parse("lnurl").ok()?;
+    bech32::encode::<bech32::Bech32>(hrp, url.as_bytes()).ok()
+}
+
+pub async fn fetch_pay_params(url: &str) -> Result<LnurlPay, String> {
+    let response = reqwest::get(url).await.map_err(|e| e.to_string())?;
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    serde_json::from_str(&text).map_err(|_| {
+        serde_json::from_str::<LnurlResponse>(&text)
+            .ok()
+            .and_then(|r| r.reason)
+            .unwrap_or_else(|| "invalid LNURL-pay response".to_string())
+    })
+}
+
+/// Unsigned kind-9734 for `amount_msat` to `recipient`, optionally about `note`.
+pub fn zap_request(
+    recipient: PublicKey,
+    note: Option<EventId>,
+    relays: Vec<String>,
+    amount_msat: u64,
+    lnurl: Option<String>,
+    comment: &str,
+) -> EventBuilder {
+    let mut data = ZapRequestData::new(recipient, relays.into_iter().map(UncheckedUrl::from))
+        .amount(amount_msat)
+        .message(comment);
+    if let Some(lnurl) = lnurl {
+        data = data.lnurl(lnurl);
+    }
+    if let Some(note) = note {
+        data = data.event_id(note);
+    }
+    EventBuilder::public_zap_request(data)
+}
+
+fn callback_url(
+    pay: &LnurlPay,
+    amount_msat: u64,
+    zap_request: &Event,
+    lnurl: Option<&str>,
+) -> Result<String, String> {
+    let mut url = Url::parse(&pay.callback).map_err(|e| e.to_string())?;
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("amount", &amount_msat.to_string());
+        query.append_pair("nostr", &zap_request.as_json());
+        if let Some(lnurl) = lnurl {
+            query.append_pair("lnurl", lnurl);
+        }
+    }
+    Ok(url.to_string())
+}
+
+/// Ask the recipient's LNURL server for an invoice paying `zap_request`.
+pub async fn fetch_invoice(
+    pay: &LnurlPay,
+    amount_msat: u64,
+    zap_request: &Event,
+    lnurl: Option<&str>,
+) -> Result<String, String> {
+    if !pay.allows_nostr {
+        return Err("wallet does not support zaps".to_string());
+    }
+    if amount_msat < pay.min_sendable || amount_msat > pay.max_sendable {
+        return Err(format!(
+            "amount must be between {} and {} sats",
+            pay.min_sendable / 1000,
+            pay.max_sendable / 1000
+        ));
+    }
+    let url = callback_url(pay, amount_msat, zap_request, lnurl)?;
+    let response: LnurlResponse = reqwest::get(url)
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    response.pr.ok_or_else(|| {
+        response
+            .reason
+            .unwrap_or_else(|| "no invoice returned".to_string())
+    })
+}
+
+/// A kind-9735 whose embedded zap request checks out. It only counts once
+/// `zapper` is known to be the recipient's LNURL server, see [`Zappers`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZapReceipt {
+    pub note: Option<EventId>,
+    pub recipient: PublicKey,
+    /// Signer of the receipt
+    pub zapper: PublicKey,
+    pub sats: u64,
+}
+
+impl ZapReceipt {
+    /// `server` is the recipient's LNURL `nostrPubkey`, when known.
+    pub fn signed_by(&self, server: Option<PublicKey>) -> bool {
+        server == Some(self.zapper)
+    }
+}
+
+/// NIP-57 receipt checks that need nothing but the event: the `description` is
+/// a signed 9734 for the same recipient and note, and the invoice pays the
+/// amount it asked for.
+pub fn zap_receipt(event: &Event) -> Option<ZapReceipt> {
+    if event.kind != Kind::ZapReceipt {
+        return None;
+    }
+    let bolt11 = tag_value(event, "bolt11")?;
+    let request = Event::from_json(tag_value(event, "description")?).ok()?;
+    if request.kind != Kind::ZapRequest || request.verify().is_err() {
+        return None;
+    }
+    let mut recipients = request.public_keys();
+    let recipient = *recipients.next()?;
+    if recipients.next().is_some() || event.public_keys().next() != Some(&recipient) {
+        return None;
+    }
+    let note = request.event_ids().next().copied();
+    if event.event_ids().next().copied() != note {
+        return None;
+    }
+    let msat = bolt11_amount_msat(&bolt11)?;
+    if let Some(amount) = tag_value(&request, "amount") {
+        if amount.parse::<u64>().ok() != Some(msat) {
+            return None;
+        }
+    }
+    Some(ZapReceipt {
+        note,
+        recipient,
+        zapper: event.pubkey,
+        sats: msat / 1000,
+    })
+}
+
+/// The `nostrPubkey` of each recipient's LNURL server, the only key allowed to
+/// sign their zap receipts. Shared through context.
+#[derive(Clone, Copy)]
+pub struct Zappers {
+    keys: RwSignal<HashMap<PublicKey, Option<PublicKey>>>,
+    requested: StoredValue<HashSet<PublicKey>>,
+    profiles: ProfileCache,
+}
+
+impl Zappers {
+    pub fn new(profiles: ProfileCache) -> Self {
+        Self {
+            keys: create_rw_signal(HashMap::new()),
+            requested: store_value(HashSet::new()),
+            profiles,
+        }
+    }
+
+    /// Tracked. `None` until the recipient's profile and LNURL server have
+    /// answered, and for wallets that do not support zaps.
+    pub fn get(&self, recipient: &PublicKey) -> Option<PublicKey> {
+        if let Some(key) = self.keys.with(|keys| keys.get(recipient).copied()) {
+            return key;
+        }
+        // reruns once the kind-0 arrives
+        let url = self
+            .profiles
+            .get(recipient)
+            .and_then(|profile| lnurl_pay_url(&profile.metadata))?;
+        self.lookup(*recipient, url);
+        None
+    }
+
+    /// `true` when `receipt` was signed by the recipient's LNURL server. Tracked.
+    pub fn verifies(&self, receipt: &ZapReceipt) -> bool {
+        receipt.signed_by(self.get(&receipt.recipient))
+    }
+
+    fn lookup(&self, recipient: PublicKey, url: String) {
+        let first = self
+            .requested
+            .try_update_value(|requested| requested.insert(recipient))
+            .unwrap_or(false);
+        if !first {
+            return;
+        }
+        let keys = self.keys;
+        spawn_local(async move {
+            let key = match fetch_pay_params(&url).await {
+                Ok(pay) if pay.allows_nostr => pay
+                    .nostr_pubkey
+                    .as_deref()
+                    .and_then(|key| PublicKey::from_hex(key).ok()),
+                Ok(_) => None,
+                Err(e) => {
+                    log!("lnurl {}: {}", url, e);
+                    None
+                }
+            };
+            keys.try_update(|keys| keys.insert(recipient, key));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn lud16_and_lud06() {
+        let metadata = Metadata::new().lud16("Vaz@siamstr.com");
+        assert_eq!(
+            lnurl_pay_url(&metadata).as_deref(),
+            Some("https://siamstr.com/.well-known/lnurlp/vaz")
+        );
+
+        let url = "https://siamstr.com/.well-known/lnurlp/vaz";
+        let lnurl = encode_lnurl(url).unwrap();
+        assert!(lnurl.starts_with("lnurl1"));
+        let metadata = Metadata::new().lud06(lnurl.to_uppercase());
+        assert_eq!(lnurl_pay_url(&metadata).as_deref(), Some(url));
+
+        assert_eq!(lnurl_pay_url(&Metadata::new()), None);
+    }
+
+    fn request(recipient: &Keys, note: EventId, amount_msat: u64) -> Event {
+        EventBuilder::public_zap_request(
+            ZapRequestData::new(recipient.public_key(), [])
+                .event_id(note)
+                .amount(amount_msat),
+        )
+        .to_event(&Keys::generate())
+        .unwrap()
+    }
+
+    #[test]
+    fn receipt_is_tallied_from_bolt11() {
+        let recipient = Keys::generate();
+        let zapper = Keys::generate();
+        let note = EventId::all_zeros();
+        let receipt = EventBuilder::zap_receipt(
+            "lnbc210n1pjexample",
+            None::<String>,
+            &request(&recipient, note, 21_000),
+        )
+        .to_event(&zapper)
+        .unwrap();
+        assert_eq!(
+            zap_receipt(&receipt),
+            Some(ZapReceipt {
+                note: Some(note),
+                recipient: recipient.public_key(),
+                zapper: zapper.public_key(),
+                sats: 21,
+            })
+        );
+    }
+
+    #[test]
+    fn forged_receipts_are_rejected() {
+        let recipient = Keys::generate();
+        let note = EventId::all_zeros();
+        let receipt = |bolt11: &str, request: &Event| {
+            EventBuilder::zap_receipt(bolt11, None::<String>, request)
+                .to_event(&Keys::generate())
+                .unwrap()
+        };
+
+        // the invoice pays less than the request says
+        let cheap = receipt("lnbc10n1pjexample", &request(&recipient, note, 21_000));
+        assert_eq!(zap_receipt(&cheap), None);
+
+        // no zap request behind it at all
+        let fake = EventBuilder::new(
+            Kind::ZapReceipt,
+            "",
+            [
+                Tag::public_key(recipient.public_key()),
+                Tag::event(note),
+                Tag::parse(&["bolt11", "lnbc10m1pjexample"]).unwrap(),
+                Tag::parse(&["description", "{}"]).unwrap(),
+            ],
+        )
+        .to_event(&Keys::generate())
+        .unwrap();
+        assert_eq!(zap_receipt(&fake), None);
+
+        // a request with its content changed after signing
+        let mut json: serde_json::Value =
+            serde_json::from_str(&request(&recipient, note, 21_000).as_json()).unwrap();
+        json["content"] = "tampered".into();
+        let tampered = Event::from_json(json.to_string()).unwrap();
+        assert_eq!(zap_receipt(&receipt("lnbc210n1pjexample", &tampered)), None);
+
+        // well formed, but signed by someone other than the recipient's server
+        let server = Keys::generate().public_key();
+        let forged = receipt("lnbc210n1pjexample", &request(&recipient, note, 21_000));
+        let parsed = zap_receipt(&forged).unwrap();
+        assert!(!parsed.signed_by(Some(server)));
+        assert!(!parsed.signed_by(None));
+        let genuine = ZapReceipt {
+            zapper: server,
+            ..parsed
+        };
+        assert!(genuine.signed_by(Some(server)));
+    }
+
+    /// Minimal LNURL server answering `count` requests, returns its base url.
+    fn stand_in_lnurl_server(count: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let callback = format!("{}/callback", base);
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let body = if path.starts_with("/.well-known/lnurlp/") {
+                    format!(
+                        r#"{{"callback":"{}","minSendable":1000,"maxSendable":100000000,"allowsNostr":true,"nostrPubkey":"{}","tag":"payRequest"}}"#,
+                        callback,
+                        Keys::generate().public_key()
+                    )
+                } else if path.contains("nostr=") && path.contains("amount=21000") {
+                    r#"{"pr":"lnbc210n1pjstandin","routes":[]}"#.to_string()
+                } else {
+                    r#"{"status":"ERROR","reason":"bad request"}"#.to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn zap_flow_against_stand_in_server() {
+        let base = stand_in_lnurl_server(2);
+        let pay = fetch_pay_params(&format!("{}/.well-known/lnurlp/vaz", base))
+            .await
+            .unwrap();
+        assert!(pay.allows_nostr);
+        assert_eq!(pay.callback, format!("{}/callback", base));
+
+        let keys = Keys::generate();
+        let request = zap_request(keys.public_key(), None, vec![], 21_000, None, "ขอบคุณ")
+            .to_event(&keys)
+            .unwrap();
+        let invoice = fetch_invoice(&pay, 21_000, &request, None).await.unwrap();
+        assert_eq!(invoice, "lnbc210n1pjstandin");
+
+        assert!(fetch_invoice(&pay, 1, &request, None).await.is_err());
+    }
+}
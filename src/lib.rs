@@ -2,7 +2,6 @@ use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 use nostr_sdk::prelude::*;
-use std::str::FromStr;
 
 // Modules
 mod components;
@@ -10,25 +9,84 @@ mod helper;
 mod pages;
 
 // Top-Level pages
+use crate::pages::diagnostics::Diagnostics;
 use crate::pages::home::Home;
 use crate::pages::nostr_endpoint::NostrEP;
 use crate::pages::not_found::NotFound;
-const NSEC_ANON: &str = "nsec1nuq7e2w37t89apaupmxj5ylg027mnfmtzv4d2rcmjmt7fwjhhlmsxdmlq7";
+use crate::pages::profile::ProfilePage;
+use crate::pages::search::Search;
+use crate::pages::settings::Settings;
+use crate::pages::tag_feed::TagFeed;
+
+use crate::components::notifications::NotificationListener;
+use crate::helper::mutes::Mutes;
+use crate::helper::notifications::Notifier;
+use crate::helper::outbox::Outbox;
+use crate::helper::profiles::ProfileCache;
+use crate::helper::reactions::ReactionStore;
+use crate::helper::relay_status::RelayMonitor;
+use crate::helper::relays::{apply_relays, load_relays, save_relays};
+use crate::helper::signer::{guest_keys, LoginMode};
+use crate::helper::store::EventStore;
+use crate::helper::validate::Validator;
+use crate::helper::zap::Zappers;
 
 /// An app router which renders the homepage and handles 404's
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
-    let app_keys = Keys::from_str(NSEC_ANON).expect("ปลาอานนท์");
-    let app_keys_signal = create_rw_signal(app_keys);
-    let client = Client::new(app_keys_signal.get_untracked());
+    // Nothing is signed until the visitor logs in or picks guest mode
+    let app_keys_signal = create_rw_signal(guest_keys());
+    let login_mode = create_rw_signal(LoginMode::LoggedOut);
+    let client = Client::default();
     let client_signal = create_rw_signal(client);
-    let (event_text, event_text_list) = create_signal(Vec::<Event>::new());
+    let relays = create_rw_signal(load_relays());
+    let validator = Validator::new();
+    let monitor = RelayMonitor::new(client_signal);
     provide_context(app_keys_signal);
+    provide_context(login_mode);
     provide_context(client_signal);
-    provide_context(event_text);
-    provide_context(event_text_list);
+    let store = EventStore::new();
+    provide_context(store);
+    provide_context(relays);
+    provide_context(validator);
+    provide_context(monitor);
+    provide_context(Outbox::new(client_signal, store, validator));
+    let profiles = ProfileCache::new(client_signal, validator);
+    provide_context(profiles);
+    let zappers = Zappers::new(profiles);
+    provide_context(ReactionStore::new(client_signal, validator, zappers));
+    let mutes = Mutes::new(client_signal, validator, relays);
+    provide_context(mutes);
+    provide_context(Notifier::new(client_signal, validator, zappers));
+
+    // only a personal key has a mute list worth loading
+    create_effect(move |_| match login_mode.get() {
+        LoginMode::Extension => mutes.load(app_keys_signal.with(|keys| keys.public_key())),
+        _ => mutes.clear(),
+    });
+    // a load that reached no relay is tried again as relays connect
+    let connected = create_memo(move |_| monitor.connected());
+    create_effect(move |_| {
+        if connected.get() > 0 {
+            mutes.retry();
+        }
+    });
+
+    // one relay list for every page, re-applied to the shared pool on each edit
+    create_effect(move |_| {
+        let relays_ = relays.get();
+        save_relays(&relays_);
+        let client = client_signal.get_untracked();
+        spawn_local(async move {
+            for (url, e) in apply_relays(&client, &relays_).await {
+                if let Ok(url) = nostr_sdk::Url::parse(&url) {
+                    monitor.record_error(&url, e);
+                }
+            }
+        });
+    });
 
     view! {
         <Html lang="en" dir="ltr" attr:data-theme="dark"/>
@@ -41,10 +99,16 @@ pub fn App() -> impl IntoView {
         <Meta name="viewport" content="width=device-width, initial-scale=1.0"/>
 
         <Router>
+            <NotificationListener/>
             <Routes>
                 <Route path="/" view=Home/>
                 <Route path="/*" view=NotFound/>
                 <Route path="/nostr/:id" view=NostrEP/>
+                <Route path="/t/:tag" view=TagFeed/>
+                <Route path="/p/:npub" view=ProfilePage/>
+                <Route path="/search" view=Search/>
+                <Route path="/settings" view=Settings/>
+                <Route path="/diagnostics" view=Diagnostics/>
             </Routes>
         </Router>
     }
@@ -0,0 +1,53 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::address::{AddressFallback, CalendarCard, ListCard, LiveEventCard};
+use crate::components::loading::LoadingIndi;
+use crate::helper::address::{address_identifier, AddressKind};
+use crate::helper::mutes::Mutes;
+use crate::helper::outbox::Outbox;
+use crate::helper::store::EventStore;
+use crate::pages::article::ArticleBody;
+
+/// Target of an `naddr`: fetched by kind, author and `d` tag, relay hints first,
+/// then the author's write relays, then drawn by kind.
+#[component]
+pub fn AddressView(coordinate: Coordinate) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let (not_found, set_not_found) = create_signal(false);
+    let kind = coordinate.kind;
+    let author = coordinate.public_key;
+    let identifier = address_identifier(&coordinate).map(str::to_string);
+
+    let hints = outbox.hold_hints(&coordinate.relays);
+    let held = hints.clone();
+    on_cleanup(move || outbox.release_hints(&held));
+
+    spawn_local(async move {
+        let hinted = outbox.connect_hints(&hints).await;
+        let missing = outbox.fetch_address(&coordinate, hinted).await.is_none();
+        let _ = set_not_found.try_set(missing);
+    });
+
+    let event = move || {
+        store
+            .replaceable(kind, author, identifier.as_deref())
+            .filter(|event| !mutes.hides(event))
+    };
+
+    move || match event() {
+        Some(event) => match AddressKind::of(event.kind) {
+            AddressKind::Article => view! { <ArticleBody event=event/> }.into_view(),
+            AddressKind::LiveEvent => view! { <LiveEventCard event=event/> }.into_view(),
+            AddressKind::List => view! { <ListCard event=event/> }.into_view(),
+            AddressKind::Calendar => view! { <CalendarCard event=event/> }.into_view(),
+            AddressKind::Other => view! { <AddressFallback event=event/> }.into_view(),
+        },
+        None if not_found.get() => {
+            view! { <p class="p-6 text-gray-500">"ไม่พบรายการนี้"</p> }.into_view()
+        }
+        None => view! { <div class="p-6"><LoadingIndi/></div> }.into_view(),
+    }
+}
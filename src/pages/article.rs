@@ -0,0 +1,64 @@
+use leptos::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::{AuthorName, Profiles};
+use crate::components::note_actions::NoteActions;
+use crate::helper::article::{markdown_to_html, Article};
+use crate::helper::profiles::ProfileCache;
+
+/// Link text for a `nostr:` reference, `@name` once the profile is known.
+fn reference_label(profiles: ProfileCache, entity: &str) -> String {
+    let public_key = match Nip19::from_bech32(entity) {
+        Ok(Nip19::Pubkey(pk)) => Some(pk),
+        Ok(Nip19::Profile(profile)) => Some(profile.public_key),
+        _ => None,
+    };
+    match public_key
+        .and_then(|pk| profiles.get(&pk))
+        .and_then(|p| p.name())
+    {
+        Some(name) => format!("@{}", name),
+        None => format!("{}…", &entity[..entity.len().min(16)]),
+    }
+}
+
+/// A NIP-23 article: cover, title, summary and the Markdown body.
+#[component]
+pub fn ArticleBody(event: Event) -> impl IntoView {
+    let profiles = use_context::<ProfileCache>().expect("profile cache init");
+    let Some(article) = Article::from_event(&event) else {
+        return ().into_view();
+    };
+    let profile = event.pubkey.to_hex();
+    let published = article
+        .published_at
+        .unwrap_or(event.created_at)
+        .to_human_datetime();
+    let content = article.content.clone();
+    let body = move || markdown_to_html(&content, |entity| reference_label(profiles, entity));
+    view! {
+        <article class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 min-h-screen">
+            {article
+                .image
+                .clone()
+                .map(|url| view! { <img src=url alt="cover" class="w-full max-h-80 object-cover rounded-t-lg"/> })}
+            <div class="p-6 flex flex-col space-y-4 text-gray-700 dark:text-gray-200">
+                <h1 class="text-3xl font-bold">
+                    {article.title.clone().unwrap_or_else(|| article.identifier.clone())}
+                </h1>
+                <div class="flex items-center space-x-2 text-xs">
+                    <Profiles profile=profile.clone() order=1/>
+                    <AuthorName profile=profile/>
+                    <span class="text-gray-400">{published}</span>
+                </div>
+                {article
+                    .summary
+                    .clone()
+                    .map(|summary| view! { <p class="italic text-gray-500">{summary}</p> })}
+                <div class="article-body" inner_html=body></div>
+                <NoteActions note=event.clone()/>
+            </div>
+        </article>
+    }
+    .into_view()
+}
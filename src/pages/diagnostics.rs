@@ -0,0 +1,60 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::components::relay_status::RelayStatusPanel;
+use crate::helper::validate::{Rejection, Validator};
+
+/// Relay connection status, then accepted and dropped events per relay.
+#[component]
+pub fn Diagnostics() -> impl IntoView {
+    let validator = use_context::<Validator>().expect("validator init");
+
+    view! {
+        <div class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 p-6 min-h-screen">
+            <div class="flex items-center justify-between h-8 border-b-2 border-gray-200 mb-4">
+                <span class="text-2xl text-gray-700 dark:text-purple-600">"Diagnostics"</span>
+                <A href="/settings" class="text-sm text-gray-600 dark:text-purple-200">"← Relays"</A>
+            </div>
+            <RelayStatusPanel/>
+            <table class="w-full text-sm text-left text-gray-600 dark:text-gray-300">
+                <thead>
+                    <tr>
+                        <th class="py-2">"Relay"</th>
+                        <th class="py-2 text-right">"ผ่าน"</th>
+                        {Rejection::ALL
+                            .into_iter()
+                            .map(|reason| view! { <th class="py-2 text-right">{reason.label()}</th> })
+                            .collect_view()}
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=move || validator.counts()
+                        key=|(relay, counts)| (relay.clone(), counts.clone())
+                        children=move |(relay, counts)| {
+                            let relay = relay.to_string();
+                            view! {
+                                <tr class="border-t border-gray-200 dark:border-gray-700">
+                                    <td class="py-2 break-all">{relay}</td>
+                                    <td class="py-2 text-right">{counts.accepted}</td>
+                                    {Rejection::ALL
+                                        .into_iter()
+                                        .map(|reason| {
+                                            let n = counts.rejected(reason);
+                                            let class_list = if n > 0 {
+                                                "py-2 text-right text-red-500 font-bold"
+                                            } else {
+                                                "py-2 text-right"
+                                            };
+                                            view! { <td class=class_list>{n}</td> }
+                                        })
+                                        .collect_view()}
+                                </tr>
+                            }
+                        }
+                    />
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -1,235 +1,345 @@
 // use crate::components::loading::LoadingIndi;
 use std::collections::{HashMap, HashSet};
-use std::ops::Sub;
-use std::str::FromStr;
+use std::time::Duration;
 
 use crate::components::chats::{ChatOther, ChatSelf};
-use crate::helper::note_regex::transform_text_to_html;
-use leptos::{
-    html::{AnyElement, Div},
-    *,
-};
+use crate::components::composer::Composer;
+use crate::components::login::LoginBar;
+use crate::components::note_content::{article_preview, transform_text_to_html};
+use crate::helper::mutes::Mutes;
+use crate::helper::pagination::{FeedHistory, PAGE_SIZE};
+use crate::helper::publish::PublishState;
+use crate::helper::relays::RelayConfig;
+use crate::helper::signer::use_self_pubkey;
+use crate::helper::store::{EventStore, IndexedTag};
+use crate::helper::validate::Validator;
+use leptos::logging::log;
+use leptos::{html::Div, *};
+use leptos_router::A;
 use nostr_sdk::prelude::*;
 
+const PAGE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Distance from the top of #messages that asks for the next older page.
+const SCROLL_THRESHOLD: i32 = 64;
+
+fn feed_filter(tags: &[String]) -> Filter {
+    Filter::new()
+        .kind(Kind::TextNote)
+        .hashtags(tags)
+        .limit(PAGE_SIZE)
+}
+
+/// Put `node` after the last bubble that is not newer, returns `false` when it
+/// landed above existing bubbles.
+fn insert_by_time(container: &web_sys::Element, node: &web_sys::Element, created_at: u64) -> bool {
+    let _ = node.set_attribute("data-created", &created_at.to_string());
+    let mut child = container.last_element_child();
+    let mut is_last = true;
+    while let Some(el) = child {
+        let t = el
+            .get_attribute("data-created")
+            .and_then(|t| t.parse::<u64>().ok())
+            .unwrap_or_default();
+        if t <= created_at {
+            let _ = container.insert_before(node, el.next_sibling().as_ref());
+            return is_last;
+        }
+        is_last = false;
+        child = el.previous_element_sibling();
+    }
+    let _ = container.prepend_with_node_1(node);
+    is_last
+}
+
+/// Live chat of notes carrying any of `tags`, lowercase and without `#`.
 #[component]
-pub fn Feed() -> impl IntoView {
-    let app_keys = use_context::<RwSignal<Keys>>().expect("app key init");
-    let event_text = use_context::<ReadSignal<Vec<Event>>>().expect("event read init");
-    let event_text_list = use_context::<WriteSignal<Vec<Event>>>().expect("event write init");
-    let pk = create_rw_signal(app_keys.get_untracked().public_key().to_hex());
-    let (event_metadata, event_metadata_list) = create_signal(HashMap::<String, Metadata>::new());
+pub fn Feed(tags: Vec<String>) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let validator = use_context::<Validator>().expect("validator init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let relays = use_context::<RwSignal<Vec<RelayConfig>>>().expect("relays init");
+    let pk = use_self_pubkey();
     let container_ref = create_node_ref::<Div>();
+    let pending = create_rw_signal(HashMap::<EventId, PublishState>::new());
+    // bubbles are built inside an effect, keep them owned by the page so they stay reactive
+    let owner = Owner::current().expect("feed owner");
     let added_events = create_signal(HashSet::<String>::new());
-    let client = use_context::<RwSignal<Client>>()
-        .expect("app key init")
-        .get_untracked();
+    let client_signal = use_context::<RwSignal<Client>>().expect("app key init");
+    let client = client_signal.get_untracked();
+    let history = create_rw_signal(FeedHistory::default());
+    let page = store_value(0_u64);
+    // live subscription first, then one per relay per older page
+    let subs = store_value(Vec::<SubscriptionId>::new());
+    let ours = move |id: &SubscriptionId| subs.with_value(|subs| subs.contains(id));
+    let title = tags
+        .iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let composer_tags = tags.clone();
+    let tags = store_value(tags);
 
+    let load_older = move || {
+        let Some(cursors) = history.try_update(|h| h.next_page()) else {
+            return;
+        };
+        if cursors.is_empty() {
+            return;
+        }
+        let current = page.try_update_value(|p| {
+            *p += 1;
+            *p
+        });
+        set_timeout(
+            move || {
+                if page.try_get_value() == current {
+                    history.update(|h| h.expire());
+                }
+            },
+            PAGE_TIMEOUT,
+        );
+        let client = client_signal.get_untracked();
+        for (url, until) in cursors {
+            let id = SubscriptionId::generate();
+            subs.update_value(|subs| subs.push(id.clone()));
+            let client = client.clone();
+            spawn_local(async move {
+                let opts = SubscribeAutoCloseOptions::default()
+                    .filter(FilterOptions::ExitOnEOSE)
+                    .timeout(Some(PAGE_TIMEOUT));
+                let filter = tags.with_value(|tags| feed_filter(tags)).until(until);
+                if let Err(e) = client
+                    .subscribe_with_id_to([url.clone()], id, vec![filter], Some(opts))
+                    .await
+                {
+                    log!("older page from {} failed: {}", url, e);
+                }
+            });
+        }
+    };
+    // after each page: land on the newest note first, and keep loading while
+    // there is not enough to scroll
+    let landed = store_value(false);
+    let page_loaded = move || {
+        let Some(div) = container_ref.get_untracked() else {
+            return;
+        };
+        if !landed.get_value() {
+            landed.set_value(true);
+            div.set_scroll_top(div.scroll_height());
+        }
+        if div.scroll_height() <= div.client_height() {
+            load_older();
+        }
+    };
+
+    let live_id = SubscriptionId::generate();
+    subs.update_value(|subs| subs.push(live_id.clone()));
+    let sub_client = client.clone();
+    let cleanup_id = live_id.clone();
     spawn_local(async move {
-        let _ = client.add_relay("ws://localhost:4869").await;
-        let _ = client.add_relay("wss://relay.siamstr.com").await;
-        let _ = client.add_relay("wss://relay.notoshi.win").await;
-        let _ = client.add_relay("wss://bostr.lecturify.net").await;
-        client.connect().await;
-        // let filters_1 = Filter::new().kinds(vec![Kind::Metadata]);
-        // let sub_id_1 = client.subscribe(vec![filters_1], None).await;
-        let filters_2 = Filter::new()
-            .kinds(vec![Kind::TextNote])
-            .hashtag("siamstr")
-            // .limit(1000)
-            .since(Timestamp::now().sub(100_000));
-        // let filters_3 = Filter::new().kinds(vec![Kind::Reaction]);
-        let sub_id_2 = client.subscribe(vec![filters_2], None).await;
-        // let sub_id_3 = client.subscribe(vec![filters_3], None).await;
+        let urls = relays.with_untracked(|relays| {
+            relays
+                .iter()
+                .filter(|r| r.read)
+                .filter_map(|r| Url::parse(&r.url).ok())
+                .collect::<Vec<_>>()
+        });
+        history.update(|h| h.start(urls));
+        set_timeout(
+            move || {
+                if history.try_with_untracked(FeedHistory::is_loading) == Some(true)
+                    && page.try_get_value() == Some(0)
+                {
+                    history.update(|h| h.expire());
+                    page_loaded();
+                }
+            },
+            PAGE_TIMEOUT,
+        );
+        client
+            .subscribe_with_id(
+                live_id,
+                vec![tags.with_value(|tags| feed_filter(tags))],
+                None,
+            )
+            .await;
         let mut notifications = client.notifications();
         while let Ok(notification) = notifications.recv().await {
-            if let RelayPoolNotification::Event {
-                subscription_id,
-                event,
-                ..
-            } = notification
-            {
-                // if subscription_id == sub_id_1 && event.kind == Kind::Metadata {
-                //     // handle the event
-                //     let json_str = format!(r#"{}"#, event.content());
-                //     let data: Metadata = serde_json::from_str(&json_str).unwrap_or(Metadata::new());
-                //     evm.insert(event.pubkey.to_hex(), data);
-                //     event_metadata_list.set(evm.clone());
-                // } else
-                if subscription_id == sub_id_2
-                // && (event.kind == Kind::TextNote || event.kind == Kind::LongFormTextNote)
-                {
-                    let mut evt = event_text.get_untracked().to_vec();
-                    evt.push(*event);
-                    event_text_list.set(evt);
+            if subs.try_with_value(|_| ()).is_none() {
+                // page is gone
+                break;
+            }
+            let alive = match notification {
+                RelayPoolNotification::Event {
+                    relay_url,
+                    subscription_id,
+                    event,
+                } if ours(&subscription_id) => {
+                    if validator.check(&relay_url, &event) {
+                        store.insert(*event);
+                    }
+                    true
                 }
+                RelayPoolNotification::Message {
+                    relay_url,
+                    message:
+                        RelayMessage::Event {
+                            subscription_id,
+                            event,
+                        },
+                } if ours(&subscription_id) => history
+                    .try_update(|h| h.on_event(&relay_url, event.created_at))
+                    .is_some(),
+                RelayPoolNotification::Message {
+                    relay_url,
+                    message: RelayMessage::EndOfStoredEvents(subscription_id),
+                } if ours(&subscription_id) => {
+                    let done = history.try_update(|h| {
+                        h.on_eose(&relay_url);
+                        !h.is_loading()
+                    });
+                    if done == Some(true) {
+                        page_loaded();
+                    }
+                    done.is_some()
+                }
+                _ => true,
+            };
+            if !alive {
+                // page is gone
+                break;
             }
         }
     });
+    on_cleanup(move || {
+        spawn_local(async move { sub_client.unsubscribe(cleanup_id).await });
+    });
 
-    create_effect(move |_| {
-        let pk_ = pk.get_untracked();
-        let events_ = event_text.get();
-        let mut p_event: u64 = Timestamp::now().as_u64();
+    create_effect(move |prev_pk: Option<Option<String>>| {
+        let pk_ = pk.get();
+        if prev_pk.is_some_and(|prev| prev != pk_) {
+            // identity changed, redraw every bubble on the right side
+            if let Some(c_ref) = container_ref.get_untracked() {
+                c_ref.set_inner_html("");
+            }
+            added_events.1.update(|set| set.clear());
+        }
+        // bubbles already drawn are skipped, a note with several of the tags shows once
+        let events_ = tags.with_value(|tags| {
+            tags.iter()
+                .flat_map(|tag| store.tagged(&IndexedTag::Hashtag(tag.clone())))
+                .collect::<Vec<_>>()
+        });
+        let Some(c_ref) = container_ref.get() else {
+            return pk_;
+        };
+        let (old_top, old_height) = (c_ref.scroll_top(), c_ref.scroll_height());
+        let mut inserted_above = false;
         for x in events_.iter() {
             if (x.kind == Kind::TextNote || x.kind == Kind::LongFormTextNote)
                 && !added_events.0.with(|set| set.contains(&x.id.to_hex()))
             {
                 let pk_i = x.pubkey.clone().to_hex();
-                let ext_contents = transform_text_to_html(x.content());
-
-                let message = if pk_i == pk_ {
-                    view! {
-                        <div class="chat-message">
-                            <ChatSelf ext_contents=ext_contents profile=pk_i/>
-                        </div>
+                // quotes inside keep their signals, build them under the feed
+                let ext_contents = with_owner(owner, || {
+                    if x.kind == Kind::LongFormTextNote {
+                        article_preview(x)
+                    } else {
+                        transform_text_to_html(x.content())
                     }
-                } else {
-                    view! {
-                        <div class="chat-message">
-                            <ChatOther ext_contents=ext_contents profile=pk_i/>
-                        </div>
+                });
+
+                let event_id = x.id;
+                let note = x.clone();
+                let muted_note = x.clone();
+                let muted = move || mutes.hides(&muted_note);
+                let message = with_owner(owner, || {
+                    if pk_.as_ref() == Some(&pk_i) {
+                        let state =
+                            Signal::derive(move || pending.with(|map| map.get(&event_id).cloned()));
+                        view! {
+                            <div class="chat-message" class:hidden=muted>
+                                <ChatSelf ext_contents=ext_contents profile=pk_i state=state note=note/>
+                            </div>
+                        }
+                    } else {
+                        view! {
+                            <div class="chat-message" class:hidden=muted>
+                                <ChatOther ext_contents=ext_contents profile=pk_i note=note/>
+                            </div>
+                        }
                     }
-                };
-                let c_ref = container_ref.get().expect("messages");
-                // .append_child(&message)
-                if p_event > x.created_at().as_u64() {
-                    c_ref.prepend_with_node_1(&message).unwrap();
-                } else {
-                    c_ref.append_child(&message).unwrap();
-                };
-                p_event = x.created_at.as_u64();
+                });
+                if !insert_by_time(&c_ref, &message, x.created_at.as_u64()) {
+                    inserted_above = true;
+                }
                 added_events.1.update(|set| {
                     set.insert(x.id.to_hex());
                 });
             }
         }
+        if inserted_above && old_top > 0 {
+            // older notes went in above, keep the reader where they were
+            c_ref.set_scroll_top(old_top + c_ref.scroll_height() - old_height);
+        }
+        pk_
     });
-    // create_effect(move |_| {
-    //     added_events.0.get();
-    //     if let Some(div) = container_ref.get() {
-    //         div.set_scroll_top(div.scroll_height())
-    //     }
-    // });
-
-    // create_effect(move |_| {
-    //     added_events.0.get();
-    //     for (npub, metadata_) in event_metadata.get().iter() {
-    //         let profile_ = metadata_.picture.clone().unwrap_or("".to_string());
-    //         let profile_node = create_node_ref::<Img>();
-    //         let profile_view = view! { <div><Profiles profile=profile_node/></div> };
-    //         profile_node
-    //             .get_untracked()
-    //             .expect("profile image")
-    //             .set_src(&profile_);
-    //         if let Some(el) = window()
-    //             .document()
-    //             .expect("window")
-    //             .get_element_by_id(npub.as_str())
-    //         {
-    //             if !el.id().is_empty() {
-    //                 if !el.replace_with_with_node_1(&profile_view).is_ok() {
-    //                     log!("replace Failed");
-    //                 } else {
-    //                     log!("replaced profile");
-    //                 }
-    //             } else {
-    //                 log!("replaced skip");
-    //             };
-    //         }
-    //     }
-    // });
 
     view! {
         <div class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 justify-items-center">
             <div class="flex-1 p:2 sm:p-6 justify-between flex flex-col h-screen">
 
-                <div class="flex items-center space-x-4 h-8 border-b-2 border-gray-200">
+                <div class="flex items-center justify-between space-x-4 h-8 border-b-2 border-gray-200">
                     <div class="flex flex-col leading-tight">
                         <div class="text-2xl mt-1 flex items-center">
                             <span class="text-gray-700 dark:text-purple-600 mr-3">
                                 "Nostr Feed"
                             </span>
                             <span class="text-lg text-gray-600 dark:text-purple-200">
-                                "#siamstr"
+                                {title}
                             </span>
                         </div>
                     </div>
+                    <div class="flex items-center space-x-2">
+                        <LoginBar/>
+                        <A href="/search" class="text-xs text-gray-600 dark:text-purple-200">
+                            "ค้นหา"
+                        </A>
+                        <A href="/settings" class="text-xs text-gray-600 dark:text-purple-200">
+                            "Relays"
+                        </A>
+                    </div>
                 </div>
 
+                <div class="text-center text-xs text-gray-400">
+                    {move || {
+                        history
+                            .with(|h| {
+                                if h.is_loading() {
+                                    Some("กำลังโหลดโน้ตเก่า...")
+                                } else if h.is_exhausted() {
+                                    Some("ไม่มีโน้ตเก่ากว่านี้แล้ว")
+                                } else {
+                                    None
+                                }
+                            })
+                    }}
+                </div>
                 <div
                     id="messages"
                     class="flex flex-col space-y-4 p-3 overflow-y-auto scrollbar-thumb-blue scrollbar-thumb-rounded scrollbar-track-blue-lighter scrollbar-w-2 scrolling-touch h-screen"
                     node_ref=container_ref
+                    on:scroll=move |_| {
+                        let near_top = container_ref
+                            .get_untracked()
+                            .is_some_and(|div| div.scroll_top() < SCROLL_THRESHOLD);
+                        if near_top {
+                            load_older();
+                        }
+                    }
                 ></div>
-                <div class="border-t-2 border-gray-200 px-4 pt-4 mb-2 sm:mb-0 z-1">
-                    <div class="relative flex">
-                        <input
-                            type="text"
-                            placeholder="เกิดอะไรขึ้น??   ส่งข้อความด้วยปลาอานนท์"
-                            class="w-full focus:outline-none focus:placeholder-gray-400 text-gray-600 placeholder-gray-600 pl-12 bg-gray-200 rounded-md py-3"
-                        />
-                        <div class="absolute right-0 items-center inset-y-0">
-                            <button
-                                type="button"
-                                class="inline-flex items-center justify-center rounded-full h-10 w-10 transition duration-500 ease-in-out text-gray-500 hover:bg-gray-300 focus:outline-none"
-                            >
-                                <svg
-                                    xmlns="http://www.w3.org/2000/svg"
-                                    fill="none"
-                                    viewBox="0 0 24 24"
-                                    stroke="currentColor"
-                                    class="h-6 w-6 text-gray-600"
-                                >
-                                    <path
-                                        stroke-linecap="round"
-                                        stroke-linejoin="round"
-                                        stroke-width="2"
-                                        d="M3 9a2 2 0 012-2h.93a2 2 0 001.664-.89l.812-1.22A2 2 0 0110.07 4h3.86a2 2 0 011.664.89l.812 1.22A2 2 0 0018.07 7H19a2 2 0 012 2v9a2 2 0 01-2 2H5a2 2 0 01-2-2V9z"
-                                    ></path>
-                                    <path
-                                        stroke-linecap="round"
-                                        stroke-linejoin="round"
-                                        stroke-width="2"
-                                        d="M15 13a3 3 0 11-6 0 3 3 0 016 0z"
-                                    ></path>
-                                </svg>
-                            </button>
-                            <button
-                                type="button"
-                                class="inline-flex items-center justify-center rounded-full h-10 w-10 transition duration-500 ease-in-out text-gray-500 hover:bg-gray-300 focus:outline-none"
-                            >
-                                <svg
-                                    xmlns="http://www.w3.org/2000/svg"
-                                    fill="none"
-                                    viewBox="0 0 24 24"
-                                    stroke="currentColor"
-                                    class="h-6 w-6 text-gray-600"
-                                >
-                                    <path
-                                        stroke-linecap="round"
-                                        stroke-linejoin="round"
-                                        stroke-width="2"
-                                        d="M14.828 14.828a4 4 0 01-5.656 0M9 10h.01M15 10h.01M21 12a9 9 0 11-18 0 9 9 0 0118 0z"
-                                    ></path>
-                                </svg>
-                            </button>
-                            <button
-                                type="button"
-                                class="inline-flex items-center justify-center rounded-lg px-4 py-3 transition duration-500 ease-in-out text-white bg-blue-500 hover:bg-blue-400 focus:outline-none"
-                            >
-                                <span class="font-bold">"Send"</span>
-                                <svg
-                                    xmlns="http://www.w3.org/2000/svg"
-                                    viewBox="0 0 20 20"
-                                    fill="currentColor"
-                                    class="h-6 w-6 ml-2 transform rotate-90"
-                                >
-                                    <path d="M10.894 2.553a1 1 0 00-1.788 0l-7 14a1 1 0 001.169 1.409l5-1.429A1 1 0 009 15.571V11a1 1 0 112 0v4.571a1 1 0 00.725.962l5 1.428a1 1 0 001.17-1.408l-7-14z"></path>
-                                </svg>
-                            </button>
-                        </div>
-                    </div>
-                </div>
+                <Composer pending=pending tags=composer_tags/>
             </div>
         </div>
     }
@@ -249,7 +359,7 @@ pub fn Feed() -> impl IntoView {
 //         // add chat here!!!
 //         {move || {
 //             let pk_ = pk.get_untracked();
-//             let events_ = event_text.get();
+//             let events_ = store.tagged(&IndexedTag::Hashtag(FEED_HASHTAG.to_string()));
 //             let metadata_ = event_metadata.get().clone();
 //             events_
 //                 .iter()
@@ -1,11 +1,12 @@
  use leptos::*;
+ use crate::helper::publish::FEED_HASHTAG;
  use crate::pages::feed::Feed;
 
 #[component]
 pub fn Home() -> impl IntoView {
 	view! {
 		<div>
-			<Feed/>
+			<Feed tags=vec![FEED_HASHTAG.to_string()]/>
 		</div>
 	}    
 }
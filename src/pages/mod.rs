@@ -1,4 +1,12 @@
+pub mod address;
+pub mod article;
+pub mod diagnostics;
 pub mod feed;
 pub mod home;
 pub mod nostr_endpoint;
 pub mod not_found;
+pub mod profile;
+pub mod search;
+pub mod settings;
+pub mod tag_feed;
+pub mod thread;
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use leptos_router::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::ChatOther;
+use crate::components::loading::LoadingIndi;
+use crate::components::note_content::transform_text_to_html;
+use crate::components::share::{ShareButton, ShareTarget};
+use crate::helper::mutes::Mutes;
+use crate::helper::outbox::Outbox;
+use crate::helper::pagination::PAGE_SIZE;
+use crate::helper::profiles::{
+    follower_count, following_count, short_npub, website_url, ProfileCache,
+};
+use crate::helper::store::EventStore;
+use crate::helper::validate::Validator;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Kind-3 lists asked for when counting followers, the count shows as "n+" past it.
+const FOLLOWER_LIMIT: usize = 1000;
+
+#[derive(Params, PartialEq)]
+struct ProfileParam {
+    npub: String,
+}
+
+/// npub, nprofile or hex, with or without `nostr:`.
+fn parse_author(input: &str) -> Option<PublicKey> {
+    let input = input.trim().trim_start_matches("nostr:");
+    match Nip19::from_bech32(input) {
+        Ok(Nip19::Pubkey(pk)) => Some(pk),
+        Ok(Nip19::Profile(profile)) => Some(profile.public_key),
+        _ => PublicKey::from_hex(input).ok(),
+    }
+}
+
+/// `/p/:npub`
+#[component]
+pub fn ProfilePage() -> impl IntoView {
+    let params = use_params::<ProfileParam>();
+    let author = move || {
+        params.with(|params| {
+            params
+                .as_ref()
+                .ok()
+                .and_then(|param| parse_author(&param.npub))
+        })
+    };
+    // the router keeps this page when only the npub changes, start a fresh view
+    move || match author() {
+        Some(author) => view! { <ProfileView author=author/> }.into_view(),
+        None => view! { <p class="p-6 text-gray-500">"ไม่พบผู้ใช้นี้"</p> }.into_view(),
+    }
+}
+
+/// Banner, kind-0 fields, follow counts and the author's notes, newest first,
+/// older pages on demand. `relays` are hints from an `nprofile`, held while the
+/// profile is open.
+#[component]
+pub fn ProfileView(author: PublicKey, #[prop(optional)] relays: Vec<String>) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let validator = use_context::<Validator>().expect("validator init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let profiles = use_context::<ProfileCache>().expect("profile cache init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let until = create_rw_signal(None::<Timestamp>);
+    let (loading, set_loading) = create_signal(false);
+    let (exhausted, set_exhausted) = create_signal(false);
+    let followers = create_rw_signal(None::<usize>);
+    // hinted and write relays of the author, asked next to the user's own
+    let routes = store_value(Vec::<nostr_sdk::Url>::new());
+
+    let load_page = move || {
+        if loading.get_untracked() || exhausted.get_untracked() {
+            return;
+        }
+        set_loading.set(true);
+        let mut filter = Filter::new()
+            .author(author)
+            .kind(Kind::TextNote)
+            .limit(PAGE_SIZE);
+        if let Some(until) = until.get_untracked() {
+            filter = filter.until(until);
+        }
+        let routes = routes.get_value();
+        spawn_local(async move {
+            match outbox.fetch(&routes, vec![filter], FETCH_TIMEOUT).await {
+                Ok(events) => {
+                    let events: Vec<Event> = events
+                        .into_iter()
+                        .filter(|event| event.pubkey == author)
+                        .collect();
+                    match events.iter().map(|event| event.created_at).min() {
+                        Some(oldest) => {
+                            let _ = until
+                                .try_set(Some(Timestamp::from(oldest.as_u64().saturating_sub(1))));
+                        }
+                        None => {
+                            let _ = set_exhausted.try_set(true);
+                        }
+                    }
+                    for event in events {
+                        store.insert(event);
+                    }
+                }
+                Err(e) => log!("notes of {} failed: {}", author, e),
+            }
+            let _ = set_loading.try_set(false);
+        });
+    };
+
+    let hints = outbox.hold_hints(&relays);
+    let held = hints.clone();
+    on_cleanup(move || outbox.release_hints(&held));
+
+    spawn_local(async move {
+        // join the hinted and write relays first so the first page reaches them
+        let mut urls = outbox.connect_hints(&hints).await;
+        urls.extend(outbox.write_relays(&[author]).await);
+        routes.set_value(urls);
+        load_page();
+    });
+    let stats_client = client.get_untracked();
+    spawn_local(async move {
+        let contacts = Filter::new().author(author).kind(Kind::ContactList);
+        match validator
+            .fetch(&stats_client, None, vec![contacts], FETCH_TIMEOUT)
+            .await
+        {
+            Ok(events) => events
+                .into_iter()
+                .filter(|event| event.pubkey == author)
+                .for_each(|event| {
+                    store.insert(event);
+                }),
+            Err(e) => log!("contacts of {} failed: {}", author, e),
+        }
+        let fans = Filter::new()
+            .kind(Kind::ContactList)
+            .pubkey(author)
+            .limit(FOLLOWER_LIMIT);
+        match validator
+            .fetch(&stats_client, None, vec![fans], FETCH_TIMEOUT)
+            .await
+        {
+            Ok(events) => {
+                let _ = followers.try_set(Some(follower_count(&events, &author)));
+            }
+            Err(e) => log!("followers of {} failed: {}", author, e),
+        }
+    });
+
+    let profile = move || profiles.get(&author);
+    let name = move || {
+        profile()
+            .and_then(|p| p.name())
+            .unwrap_or_else(|| short_npub(&author))
+    };
+    let following = move || {
+        store
+            .replaceable(Kind::ContactList, author, None)
+            .map(|contacts| following_count(&contacts).to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let follower_text = move || match followers.get() {
+        Some(n) if n >= FOLLOWER_LIMIT => format!("{}+", n),
+        Some(n) => n.to_string(),
+        None => "-".to_string(),
+    };
+    let notes = move || {
+        let mut notes: Vec<Event> = store
+            .by_author(&author)
+            .into_iter()
+            .filter(|event| event.kind == Kind::TextNote && !mutes.hides(event))
+            .collect();
+        notes.reverse();
+        notes
+    };
+
+    view! {
+        <div class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 min-h-screen">
+            {move || {
+                profile()
+                    .and_then(|p| p.metadata.banner)
+                    .filter(|url| !url.is_empty())
+                    .map(|url| view! { <img src=url alt="banner" class="w-full h-40 object-cover rounded-t-lg"/> })
+            }}
+            <div class="p-6 flex flex-col space-y-3 text-gray-700 dark:text-gray-200">
+                <div class="flex items-center space-x-4">
+                    {move || {
+                        profile()
+                            .and_then(|p| p.metadata.picture)
+                            .filter(|url| !url.is_empty())
+                            .map(|url| view! { <img src=url alt="avatar" class="w-20 h-20 rounded-full object-cover"/> })
+                    }}
+                    <div class="flex flex-col">
+                        <span class="text-2xl font-bold">{name}</span>
+                        {move || {
+                            profile()
+                                .and_then(|p| p.metadata.nip05)
+                                .map(|nip05| view! { <span class="text-sm text-purple-400">{nip05}</span> })
+                        }}
+                        <span class="text-xs text-gray-400 break-all">
+                            {author.to_bech32().unwrap_or_default()}
+                        </span>
+                    </div>
+                </div>
+                {move || {
+                    profile()
+                        .and_then(|p| p.metadata.about)
+                        .map(|about| view! { <p class="text-sm whitespace-pre-wrap">{transform_text_to_html(&about)}</p> })
+                }}
+                <div class="flex flex-wrap gap-x-4 text-sm">
+                    {move || {
+                        profile()
+                            .and_then(|p| p.metadata.website)
+                            .and_then(|website| website_url(&website))
+                            .map(|url| view! { <a href=url.clone() target="_blank" class="text-blue-500">{url}</a> })
+                    }}
+                    {move || {
+                        profile()
+                            .and_then(|p| p.metadata.lud16)
+                            .map(|lud16| view! { <span>"⚡ " {lud16}</span> })
+                    }}
+                </div>
+                <div class="flex space-x-4 text-sm">
+                    <span><b>{following}</b>" กำลังติดตาม"</span>
+                    <span><b>{follower_text}</b>" ผู้ติดตาม"</span>
+                </div>
+                <div class="flex flex-wrap items-center gap-1 text-sm">
+                    <ShareButton target=ShareTarget::Profile(author)/>
+                </div>
+            </div>
+            <div class="flex flex-col space-y-4 p-3 border-t border-gray-200 dark:border-gray-700">
+                <For
+                    each=notes
+                    key=|event| event.id
+                    children=move |event| {
+                        let profile = event.pubkey.to_hex();
+                        let ext_contents = transform_text_to_html(event.content());
+                        view! {
+                            <div class="chat-message">
+                                <ChatOther ext_contents=ext_contents profile=profile note=event/>
+                            </div>
+                        }
+                    }
+                />
+                <Show when=move || loading.get()>
+                    <LoadingIndi/>
+                </Show>
+                <Show when=move || !loading.get() && !exhausted.get() && until.with(Option::is_some)>
+                    <button
+                        class="self-center text-sm text-purple-600 dark:text-purple-300"
+                        on:click=move |_| load_page()
+                    >
+                        "โหลดโน้ตเก่ากว่านี้"
+                    </button>
+                </Show>
+                <Show when=move || exhausted.get()>
+                    <p class="text-center text-xs text-gray-400">"ไม่มีโน้ตเก่ากว่านี้แล้ว"</p>
+                </Show>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use leptos::logging::log;
+use leptos::*;
+use leptos_router::*;
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::{AuthorName, ChatOther, Profiles};
+use crate::components::loading::LoadingIndi;
+use crate::components::note_content::transform_text_to_html;
+use crate::helper::mutes::Mutes;
+use crate::helper::outbox::Outbox;
+use crate::helper::profiles::ProfileCache;
+use crate::helper::search::{
+    merge_notes, merge_profiles, note_matches, profile_matches, search_filter, search_path,
+    search_relays, search_terms, SearchMode, SEARCH_RELAYS,
+};
+use crate::helper::store::EventStore;
+
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Status {
+    Searching,
+    /// Answered by this many NIP-50 relays
+    Relays(usize),
+    /// No relay can search, matched against what this tab has loaded
+    Local,
+    Failed(String),
+}
+
+/// `/search?q=&mode=`, full-text search of notes or profiles.
+#[component]
+pub fn Search() -> impl IntoView {
+    let query = use_query_map();
+    let navigate = use_navigate();
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let q = move || query.with(|map| map.get("q").cloned().unwrap_or_default());
+    let mode = move || {
+        query.with(|map| SearchMode::from_param(map.get("mode").map_or("", String::as_str)))
+    };
+    let (input, set_input) = create_signal(q());
+
+    // the well known search relays are connected only while this page is open
+    let held = outbox.hold_hints(&SEARCH_RELAYS.map(str::to_string));
+    let release = held.clone();
+    on_cleanup(move || outbox.release_hints(&release));
+    // found on the first search, NIP-11 documents rarely change
+    let relays = store_value(None::<Vec<nostr_sdk::Url>>);
+
+    let go = move |mode: SearchMode| {
+        let query = input.get_untracked();
+        if !query.trim().is_empty() {
+            navigate(&search_path(&query, mode), Default::default());
+        }
+    };
+    let go_tab = go.clone();
+    let tab = move |tab: SearchMode, label: &'static str| {
+        let go = go_tab.clone();
+        view! {
+            <button
+                class="px-3 py-1 rounded-full text-sm"
+                class=("bg-purple-600", move || mode() == tab)
+                class=("text-white", move || mode() == tab)
+                class=("text-gray-600", move || mode() != tab)
+                class=("dark:text-purple-200", move || mode() != tab)
+                on:click=move |_| go(tab)
+            >
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <div class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 p-6 min-h-screen">
+            <div class="flex items-center justify-between h-8 border-b-2 border-gray-200 mb-4">
+                <span class="text-2xl text-gray-700 dark:text-purple-600">"ค้นหา"</span>
+                <A href="/" class="text-sm text-gray-600 dark:text-purple-200">"← กลับ"</A>
+            </div>
+            <form
+                class="flex space-x-2 mb-2"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    go(mode());
+                }
+            >
+                <input
+                    type="search"
+                    class="flex-1 px-3 py-2 rounded border border-gray-300 dark:bg-gray-700 dark:border-gray-600 dark:text-white"
+                    placeholder="ค้นหาโน้ตหรือผู้ใช้"
+                    prop:value=input
+                    on:input=move |ev| set_input.set(event_target_value(&ev))
+                />
+                <button type="submit" class="px-4 py-2 rounded bg-purple-600 text-white">
+                    "ค้นหา"
+                </button>
+            </form>
+            <div class="flex space-x-2 mb-4">
+                {tab(SearchMode::Notes, "โน้ต")} {tab(SearchMode::Profiles, "ผู้ใช้")}
+            </div>
+            // a new query or mode starts a fresh search
+            {move || {
+                let query = q();
+                if query.trim().is_empty() {
+                    view! { <p class="text-center text-sm text-gray-400">"พิมพ์คำที่ต้องการค้นหา"</p> }
+                        .into_view()
+                } else {
+                    view! {
+                        <SearchResults query=query mode=mode() held=held.clone() relays=relays/>
+                    }
+                    .into_view()
+                }
+            }}
+        </div>
+    }
+}
+
+/// Asks every NIP-50 relay and merges the answers, or matches the store and
+/// profile cache when none can search.
+#[component]
+fn SearchResults(
+    query: String,
+    mode: SearchMode,
+    held: Vec<nostr_sdk::Url>,
+    relays: StoredValue<Option<Vec<nostr_sdk::Url>>>,
+) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let profiles = use_context::<ProfileCache>().expect("profile cache init");
+    let client = use_context::<RwSignal<Client>>().expect("client init");
+    let (status, set_status) = create_signal(Status::Searching);
+    let notes = create_rw_signal(Vec::<Event>::new());
+    let authors = create_rw_signal(Vec::<PublicKey>::new());
+
+    spawn_local(async move {
+        let client = client.get_untracked();
+        let found = match relays.try_get_value().flatten() {
+            Some(found) => found,
+            None => {
+                let found = search_relays(&client, &held).await;
+                relays.try_update_value(|relays| *relays = Some(found.clone()));
+                found
+            }
+        };
+        let ready = outbox.connect_hints(&found).await;
+        if ready.is_empty() {
+            let terms = search_terms(&query);
+            match mode {
+                SearchMode::Notes => {
+                    let _ = notes.try_set(merge_notes(
+                        store
+                            .all()
+                            .into_iter()
+                            .filter(|event| note_matches(event, &terms)),
+                    ));
+                }
+                SearchMode::Profiles => {
+                    let _ = authors.try_set(
+                        profiles.find(|profile| profile_matches(&profile.metadata, &terms)),
+                    );
+                }
+            }
+            let _ = set_status.try_set(Status::Local);
+            return;
+        }
+        let filter = search_filter(&query, mode);
+        match outbox
+            .fetch_from(&ready, vec![filter], SEARCH_TIMEOUT)
+            .await
+        {
+            Ok(events) => {
+                match mode {
+                    SearchMode::Notes => {
+                        for event in &events {
+                            store.insert(event.clone());
+                        }
+                        let _ = notes.try_set(merge_notes(events));
+                    }
+                    SearchMode::Profiles => {
+                        for event in &events {
+                            profiles.insert(event);
+                        }
+                        let shown: Vec<Event> = events
+                            .into_iter()
+                            .filter(|event| !mutes.hides(event))
+                            .collect();
+                        let _ = authors.try_set(merge_profiles(&shown));
+                    }
+                }
+                let _ = set_status.try_set(Status::Relays(ready.len()));
+            }
+            Err(e) => {
+                log!("search failed: {}", e);
+                let _ = set_status.try_set(Status::Failed(e.to_string()));
+            }
+        }
+    });
+
+    let visible = move || {
+        notes
+            .get()
+            .into_iter()
+            .filter(|event| !mutes.hides(event))
+            .collect::<Vec<_>>()
+    };
+    let empty = move || {
+        !matches!(status.get(), Status::Searching | Status::Failed(_))
+            && match mode {
+                SearchMode::Notes => notes.with(Vec::is_empty),
+                SearchMode::Profiles => authors.with(Vec::is_empty),
+            }
+    };
+
+    view! {
+        <p class="text-center text-xs text-gray-400 mb-2">
+            {move || match status.get() {
+                Status::Searching => "กำลังค้นหา...".to_string(),
+                Status::Relays(count) => format!("ผลจาก {} รีเลย์ที่รองรับการค้นหา", count),
+                Status::Local => {
+                    "ไม่มีรีเลย์ที่รองรับการค้นหา (NIP-50) จึงค้นจากข้อมูลที่โหลดไว้แล้ว".to_string()
+                }
+                Status::Failed(e) => format!("ค้นหาไม่สำเร็จ: {}", e),
+            }}
+        </p>
+        <Show when=move || status.with(|status| *status == Status::Searching)>
+            <LoadingIndi/>
+        </Show>
+        <Show when=empty>
+            <p class="text-center text-sm text-gray-500">"ไม่พบผลลัพธ์"</p>
+        </Show>
+        <div class="flex flex-col space-y-4">
+            <For
+                each=visible
+                key=|event| event.id
+                children=move |event| {
+                    let profile = event.pubkey.to_hex();
+                    let ext_contents = transform_text_to_html(event.content());
+                    view! {
+                        <div class="chat-message">
+                            <ChatOther ext_contents=ext_contents profile=profile note=event/>
+                        </div>
+                    }
+                }
+            />
+            <For
+                each=move || authors.get()
+                key=|public_key| *public_key
+                children=move |public_key| {
+                    let about = move || {
+                        profiles
+                            .get(&public_key)
+                            .and_then(|profile| profile.metadata.about)
+                            .unwrap_or_default()
+                    };
+                    view! {
+                        <div class="flex items-center space-x-3 p-2 rounded hover:bg-gray-100 dark:hover:bg-gray-700">
+                            <Profiles profile=public_key.to_hex() order=1/>
+                            <div class="flex flex-col min-w-0 text-sm">
+                                <AuthorName profile=public_key.to_hex()/>
+                                <span class="truncate text-xs text-gray-500">{about}</span>
+                            </div>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,133 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::components::notifications::NotificationToggle;
+use crate::helper::relays::{default_relays, has_relay, parse_relay_url, RelayConfig};
+
+#[component]
+pub fn Settings() -> impl IntoView {
+    let relays = use_context::<RwSignal<Vec<RelayConfig>>>().expect("relays init");
+    let (new_url, set_new_url) = create_signal(String::new());
+    let (error, set_error) = create_signal(None::<&'static str>);
+
+    let add_relay = move || match parse_relay_url(&new_url.get_untracked()) {
+        Some(url) if relays.with_untracked(|list| has_relay(list, &url)) => {
+            set_error.set(Some("มีรีเลย์นี้อยู่แล้ว"));
+        }
+        Some(url) => {
+            relays.update(|list| list.push(RelayConfig::new(&url)));
+            set_new_url.set(String::new());
+            set_error.set(None);
+        }
+        None => set_error.set(Some("ต้องเป็น ws:// หรือ wss://")),
+    };
+
+    view! {
+        <div class="block w-full max-w-full bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700 p-6 min-h-screen">
+            <div class="flex items-center justify-between h-8 border-b-2 border-gray-200 mb-4">
+                <span class="text-2xl text-gray-700 dark:text-purple-600">"Relays"</span>
+                <div class="flex space-x-4 text-sm text-gray-600 dark:text-purple-200">
+                    <A href="/diagnostics">"Diagnostics"</A>
+                    <A href="/">"← กลับ"</A>
+                </div>
+            </div>
+            <table class="w-full text-sm text-left text-gray-600 dark:text-gray-300">
+                <thead>
+                    <tr>
+                        <th class="py-2">"URL"</th>
+                        <th class="py-2 text-center">"Read"</th>
+                        <th class="py-2 text-center">"Write"</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    <For
+                        each=move || relays.get()
+                        key=|relay| relay.clone()
+                        children=move |relay| {
+                            let url = relay.url.clone();
+                            let url_read = url.clone();
+                            let url_write = url.clone();
+                            let url_remove = url.clone();
+                            view! {
+                                <tr class="border-t border-gray-200 dark:border-gray-700">
+                                    <td class="py-2 break-all">{url}</td>
+                                    <td class="py-2 text-center">
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=relay.read
+                                            on:change=move |ev| {
+                                                let checked = event_target_checked(&ev);
+                                                relays.update(|list| {
+                                                    if let Some(r) = list.iter_mut().find(|r| r.url == url_read) {
+                                                        r.read = checked;
+                                                    }
+                                                });
+                                            }
+                                        />
+                                    </td>
+                                    <td class="py-2 text-center">
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=relay.write
+                                            on:change=move |ev| {
+                                                let checked = event_target_checked(&ev);
+                                                relays.update(|list| {
+                                                    if let Some(r) = list.iter_mut().find(|r| r.url == url_write) {
+                                                        r.write = checked;
+                                                    }
+                                                });
+                                            }
+                                        />
+                                    </td>
+                                    <td class="py-2 text-right">
+                                        <button
+                                            type="button"
+                                            class="rounded-lg px-3 py-1 text-white bg-red-500 hover:bg-red-400"
+                                            on:click=move |_| relays.update(|list| list.retain(|r| r.url != url_remove))
+                                        >
+                                            "ลบ"
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }
+                    />
+                </tbody>
+            </table>
+            <div class="flex mt-4 space-x-2">
+                <input
+                    type="text"
+                    placeholder="wss://relay.example.com"
+                    class="w-full focus:outline-none text-gray-600 placeholder-gray-600 px-3 bg-gray-200 rounded-md py-2"
+                    prop:value=new_url
+                    on:input=move |ev| set_new_url.set(event_target_value(&ev))
+                    on:keydown=move |ev| {
+                        if ev.key() == "Enter" {
+                            add_relay();
+                        }
+                    }
+                />
+                <button
+                    type="button"
+                    class="rounded-lg px-4 py-2 text-white bg-blue-500 hover:bg-blue-400"
+                    on:click=move |_| add_relay()
+                >
+                    "เพิ่ม"
+                </button>
+                <button
+                    type="button"
+                    class="rounded-lg px-4 py-2 text-gray-600 bg-gray-200 hover:bg-gray-300"
+                    on:click=move |_| relays.set(default_relays())
+                >
+                    "ค่าเริ่มต้น"
+                </button>
+            </div>
+            {move || error.get().map(|e| view! { <p class="text-red-500 text-sm mt-2">{e}</p> })}
+            <div class="h-8 border-b-2 border-gray-200 mt-8 mb-4">
+                <span class="text-2xl text-gray-700 dark:text-purple-600">"การแจ้งเตือน"</span>
+            </div>
+            <NotificationToggle/>
+        </div>
+    }
+}
@@ -0,0 +1,32 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::helper::publish::feed_tags;
+use crate::pages::feed::Feed;
+
+#[derive(Params, PartialEq)]
+struct TagParam {
+    tag: String,
+}
+
+/// `/t/:tag`, a feed for one hashtag or a comma-separated list of them.
+#[component]
+pub fn TagFeed() -> impl IntoView {
+    let params = use_params::<TagParam>();
+    let tags = move || {
+        params.with(|params| {
+            feed_tags(
+                params
+                    .as_ref()
+                    .map(|param| param.tag.as_str())
+                    .unwrap_or_default(),
+            )
+        })
+    };
+    // the router keeps this page when only the tag changes, start a fresh feed
+    view! {
+        <div>
+            {move || view! { <Feed tags=tags()/> }}
+        </div>
+    }
+}
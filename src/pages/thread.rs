@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use leptos::*;
+use nostr_sdk::async_utility::futures_util::future::{abortable, join};
+use nostr_sdk::prelude::*;
+
+use crate::components::chats::{ChatOther, ChatSelf};
+use crate::components::loading::LoadingIndi;
+use crate::components::note_content::transform_text_to_html;
+use crate::helper::mutes::Mutes;
+use crate::helper::outbox::Outbox;
+use crate::helper::signer::use_self_pubkey;
+use crate::helper::store::EventStore;
+use crate::helper::thread::{thread_order, thread_refs};
+use crate::helper::validate::Validator;
+
+const MAX_ANCESTORS: usize = 32;
+
+/// NIP-10 conversation around `target`: root, ancestors and every reply, nested.
+/// `relays` are hints from the link, held while the thread is open.
+#[component]
+pub fn ThreadView(
+    target: EventId,
+    #[prop(default = None)] author: Option<PublicKey>,
+    #[prop(optional)] relays: Vec<String>,
+) -> impl IntoView {
+    let store = use_context::<EventStore>().expect("event store init");
+    let validator = use_context::<Validator>().expect("validator init");
+    let mutes = use_context::<Mutes>().expect("mutes init");
+    let outbox = use_context::<Outbox>().expect("outbox init");
+    let client = use_context::<RwSignal<Client>>()
+        .expect("app key init")
+        .get_untracked();
+    let pk = use_self_pubkey();
+    let thread = create_rw_signal(HashMap::<EventId, Event>::new());
+    let root_id = create_rw_signal(None::<EventId>);
+    let (not_found, set_not_found) = create_signal(false);
+    // known before subscribing, so leaving the page early still closes it
+    let sub_id = SubscriptionId::generate();
+
+    if let Some(event) = store.get_untracked(&target) {
+        thread.update(|map| {
+            map.insert(target, event);
+        });
+    }
+
+    let hints = outbox.hold_hints(&relays);
+    let held = hints.clone();
+    on_cleanup(move || outbox.release_hints(&held));
+
+    let sub_client = client.clone();
+    let live_id = sub_id.clone();
+    let (lookup, stop) = abortable(async move {
+        let hinted = outbox.connect_hints(&hints).await;
+        let cached = thread.with_untracked(|map| map.get(&target).cloned());
+        let target_event = match cached {
+            Some(event) => event,
+            None => match outbox.fetch_event(target, author, hinted.clone()).await {
+                Some(event) => event,
+                None => {
+                    set_not_found.set(true);
+                    return;
+                }
+            },
+        };
+        let refs = thread_refs(&target_event);
+        let reader = target_event.pubkey;
+        let root = refs.root.unwrap_or(target);
+        if thread
+            .try_update(|map| map.insert(target, target_event.clone()))
+            .is_none()
+        {
+            return;
+        }
+        root_id.set(Some(root));
+
+        // walk up reply -> reply until the root or a missing note
+        let mut cursor = target_event;
+        for _ in 0..MAX_ANCESTORS {
+            let Some(parent) = thread_refs(&cursor).parent() else {
+                break;
+            };
+            let known = thread.with_untracked(|map| map.get(&parent).cloned());
+            let event = match known {
+                Some(event) => event,
+                None => match outbox.fetch_event(parent, None, hinted.clone()).await {
+                    Some(event) => event,
+                    None => break,
+                },
+            };
+            thread.update(|map| {
+                map.insert(event.id, event.clone());
+            });
+            cursor = event;
+        }
+        if !thread.with_untracked(|map| map.contains_key(&root)) {
+            if let Some(event) = outbox.fetch_event(root, None, hinted).await {
+                thread.update(|map| {
+                    map.insert(event.id, event);
+                });
+            }
+        }
+
+        let replies = Filter::new().kind(Kind::TextNote).events([root, target]);
+        // listen first, replies can arrive before `subscribe` returns
+        let mut notifications = client.notifications();
+        let mut routed_notifications = outbox.routed().notifications();
+        client
+            .subscribe_with_id(live_id.clone(), vec![replies.clone()], None)
+            .await;
+        let on_notification = |notification: RelayPoolNotification| {
+            if let RelayPoolNotification::Event {
+                relay_url,
+                subscription_id,
+                event,
+            } = notification
+            {
+                if subscription_id != live_id || !validator.check(&relay_url, &event) {
+                    return;
+                }
+                store.insert((*event).clone());
+                thread.update(|map| {
+                    map.insert(event.id, *event);
+                });
+            }
+        };
+        let mine = async {
+            while let Ok(notification) = notifications.recv().await {
+                on_notification(notification);
+            }
+        };
+        // replies tag the author, NIP-65 clients send them to the author's read
+        // relays, which the user's subscription does not reach
+        let theirs = async {
+            let read = outbox.read_relays(reader).await;
+            outbox
+                .subscribe_to(&read, live_id.clone(), vec![replies])
+                .await;
+            while let Ok(notification) = routed_notifications.recv().await {
+                on_notification(notification);
+            }
+        };
+        join(mine, theirs).await;
+    });
+    spawn_local(async move {
+        let _ = lookup.await;
+    });
+    on_cleanup(move || {
+        stop.abort();
+        spawn_local(async move {
+            sub_client.unsubscribe(sub_id.clone()).await;
+            outbox.unsubscribe(sub_id).await;
+        });
+    });
+
+    let ordered = move || {
+        let Some(root) = root_id.get() else {
+            return Vec::new();
+        };
+        thread.with(|map| {
+            thread_order(map, root)
+                .into_iter()
+                .filter_map(|(id, depth)| map.get(&id).map(|e| (e.clone(), depth)))
+                // the note asked for is shown even when muted
+                .filter(|(event, _)| event.id == target || !mutes.hides(event))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    view! {
+        <div class="flex flex-col space-y-4 p-3 overflow-y-auto scrollbar-thumb-blue scrollbar-thumb-rounded scrollbar-track-blue-lighter scrollbar-w-2 scrolling-touch h-screen">
+            <Show when=move || root_id.with(Option::is_none)>
+                {move || if not_found.get() {
+                    view! { <p class="text-gray-500">"ไม่พบโน้ตนี้"</p> }.into_view()
+                } else {
+                    view! { <LoadingIndi/> }.into_view()
+                }}
+            </Show>
+            <For
+                each=ordered
+                key=|(event, depth)| (event.id, *depth)
+                children=move |(event, depth)| {
+                    let profile = event.pubkey.to_hex();
+                    let ext_contents = transform_text_to_html(event.content());
+                    let indent = format!("margin-left: {}rem", depth.min(6) as f32 * 1.5);
+                    let class_list = if event.id == target {
+                        "chat-message rounded-lg ring-2 ring-purple-500 p-1"
+                    } else {
+                        "chat-message"
+                    };
+                    let bubble = if pk.get_untracked().as_ref() == Some(&profile) {
+                        view! { <ChatSelf ext_contents=ext_contents profile=profile note=event/> }
+                            .into_view()
+                    } else {
+                        view! { <ChatOther ext_contents=ext_contents profile=profile note=event/> }
+                            .into_view()
+                    };
+                    view! {
+                        <div class=class_list style=indent>
+                            {bubble}
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}